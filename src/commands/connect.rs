@@ -5,9 +5,9 @@ use std::time::Duration;
 use tokio::net::{TcpStream, UdpSocket};
 use tokio::time;
 
-use crate::network::resolver::resolve_hostname;
+use crate::network::resolver::resolve_target;
 
-pub async fn connect_command(host: String, port: u16, timeout: Duration, udp: bool) -> Result<()> {
+pub async fn connect_command(host: String, port: u16, timeout: Duration, udp: bool, all_addresses: bool) -> Result<()> {
     let protocol = if udp { "UDP" } else { "TCP" };
     println!("{} to {}:{}", 
         format!("🔌 {} CONNECTION TEST", protocol).bright_green().bold(), 
@@ -15,31 +15,43 @@ pub async fn connect_command(host: String, port: u16, timeout: Duration, udp: bo
         port.to_string().bright_cyan().bold()
     );
 
-    // Resolve hostname to IP
-    let ip = match resolve_hostname(&host).await {
-        Ok(ip) => {
-            if ip.to_string() != host {
-                println!("Resolved {} to {}", host.bright_cyan(), ip.to_string().bright_yellow());
-            }
-            ip
-        }
-        Err(e) => {
-            println!("{} Failed to resolve hostname: {}", "❌".red(), e);
-            return Ok(());
-        }
+    // Resolve hostname to every address, most preferred first
+    let Some(addresses) = resolve_target(&host).await else {
+        return Ok(());
     };
 
-    let addr = SocketAddr::new(ip, port);
-    println!("Testing {} connection to {}...", protocol, addr.to_string().bright_yellow());
-    println!();
-
     let start_time = std::time::Instant::now();
-    
+
     if udp {
-        test_udp_connection(addr, timeout).await
+        // Without a handshake there is no failure to fall back on
+        let targets = if all_addresses { addresses } else { vec![addresses[0]] };
+        for ip in targets {
+            let addr = SocketAddr::new(ip, port);
+            println!("Testing {} connection to {}...", protocol, addr.to_string().bright_yellow());
+            println!();
+            test_udp_connection(addr, timeout).await?;
+            println!();
+        }
     } else {
-        test_tcp_connection(addr, timeout).await
-    }?;
+        // Try each address in preference order until one connects, or all
+        // of them with --all-addresses
+        let total = addresses.len();
+        for (index, ip) in addresses.into_iter().enumerate() {
+            let addr = SocketAddr::new(ip, port);
+            println!("Testing {} connection to {}...", protocol, addr.to_string().bright_yellow());
+            println!();
+
+            let connected = test_tcp_connection(addr, timeout).await?;
+            println!();
+            if connected && !all_addresses {
+                break;
+            }
+            if !connected && !all_addresses && index + 1 < total {
+                println!("{} Falling back to the next address", "↪️".yellow());
+                println!();
+            }
+        }
+    }
 
     let elapsed = start_time.elapsed();
     println!("Connection test completed in {}ms", 
@@ -49,8 +61,13 @@ pub async fn connect_command(host: String, port: u16, timeout: Duration, udp: bo
     Ok(())
 }
 
-async fn test_tcp_connection(addr: SocketAddr, timeout: Duration) -> Result<()> {
-    match time::timeout(timeout, TcpStream::connect(addr)).await {
+/// Opens a TCP connection to `addr`; the outer error means the attempt timed out.
+pub(crate) async fn tcp_connect(addr: SocketAddr, timeout: Duration) -> Result<std::io::Result<TcpStream>, time::error::Elapsed> {
+    time::timeout(timeout, TcpStream::connect(addr)).await
+}
+
+async fn test_tcp_connection(addr: SocketAddr, timeout: Duration) -> Result<bool> {
+    let connected = match tcp_connect(addr, timeout).await {
         Ok(Ok(stream)) => {
             let local_addr = stream.local_addr().unwrap_or_else(|_| "unknown".parse().unwrap());
             println!("{} TCP connection successful!", "✅".green());
@@ -62,6 +79,7 @@ async fn test_tcp_connection(addr: SocketAddr, timeout: Duration) -> Result<()>
             if let Ok(peer_addr) = stream.peer_addr() {
                 println!("  Peer address: {}", peer_addr.to_string().bright_magenta());
             }
+            true
         }
         Ok(Err(e)) => {
             println!("{} TCP connection failed: {}", "❌".red(), e.to_string().red());
@@ -90,6 +108,7 @@ async fn test_tcp_connection(addr: SocketAddr, timeout: Duration) -> Result<()>
                     println!("  • Firewall restrictions");
                 }
             }
+            false
         }
         Err(_) => {
             println!("{} TCP connection timed out after {}s", 
@@ -102,10 +121,11 @@ async fn test_tcp_connection(addr: SocketAddr, timeout: Duration) -> Result<()>
             println!("  • Host may be unreachable");
             println!("  • Firewall may be filtering packets");
             println!("  • Network latency is very high");
+            false
         }
-    }
+    };
     
-    Ok(())
+    Ok(connected)
 }
 
 async fn test_udp_connection(addr: SocketAddr, timeout: Duration) -> Result<()> {
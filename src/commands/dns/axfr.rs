@@ -0,0 +1,290 @@
+use anyhow::Result;
+use colored::*;
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use trust_dns_resolver::proto::op::ResponseCode;
+use trust_dns_resolver::proto::rr::RData;
+
+use super::format_rdata;
+use super::targets::{authoritative_targets, server_targets, target_label};
+use crate::network::dns::{rcode_name, zone_transfer, ZoneTransfer};
+use crate::utils::format::{format_bytes, format_duration};
+
+pub async fn dns_axfr_command(
+    zone: String,
+    server: Option<String>,
+    all_ns: bool,
+    ixfr_serial: Option<u32>,
+    output: Option<String>,
+    timeout: Duration,
+) -> Result<()> {
+    let kind = if ixfr_serial.is_some() { "IXFR" } else { "AXFR" };
+    println!("{} {}", format!("📦 ZONE TRANSFER ({})", kind).bright_green().bold(), zone.bright_white().bold());
+
+    let targets = if all_ns {
+        match authoritative_targets(&zone, server.as_deref(), timeout).await {
+            Ok((apex, targets)) => {
+                if apex.trim_end_matches('.') != zone.trim_end_matches('.') {
+                    println!("{} {} is not a zone apex; the enclosing zone is {}", "⚠️".yellow(), zone, apex.bright_cyan());
+                }
+                targets
+            }
+            Err(e) => {
+                println!("{} Failed to find authoritative servers: {}", "❌".red(), e);
+                return Ok(());
+            }
+        }
+    } else {
+        match server_targets(&server.unwrap_or_default()).await {
+            Ok(targets) => targets,
+            Err(e) => {
+                println!("{} {}", "❌".red(), e);
+                return Ok(());
+            }
+        }
+    };
+
+    if targets.is_empty() {
+        println!("{} No nameservers to test", "❌".red());
+        return Ok(());
+    }
+
+    if let Some(serial) = ixfr_serial {
+        println!("Requesting changes since serial {}", serial.to_string().bright_magenta());
+    }
+    println!("Testing {} server(s) over TCP", targets.len());
+    println!();
+
+    let mut saved = false;
+    let mut summary = Vec::new();
+
+    for target in &targets {
+        let label = target_label(target);
+        println!("{} {}", "▶".bright_blue(), label.bright_white().bold());
+
+        match zone_transfer(target.server, &zone, ixfr_serial, timeout).await {
+            Ok(transfer) => {
+                display_transfer(&transfer);
+
+                if transfer.rcode == ResponseCode::NoError && !transfer.records.is_empty() {
+                    if output.is_some() && !transfer.complete {
+                        println!("  {} Not writing a zone file: the transfer ended before the closing SOA", "⚠️".yellow());
+                    } else if output.is_some() && !is_full_zone(&transfer) {
+                        println!("  {} Not writing a zone file: the server sent changes, not the full zone", "⚠️".yellow());
+                    } else if let (Some(ref path), false) = (&output, saved) {
+                        match save_zone_file(&zone, target.server, &transfer, path) {
+                            Ok(count) => {
+                                println!("  {} Wrote {} records to {}", "💾".bright_green(), count, path.bright_white());
+                                saved = true;
+                            }
+                            Err(e) => println!("  {} Failed to write zone file: {}", "❌".red(), e),
+                        }
+                    }
+                }
+
+                summary.push((label, Some(transfer)));
+            }
+            Err(e) => {
+                println!("  {} Transfer failed: {}", "❌".red(), e.to_string().red());
+                summary.push((label, None));
+            }
+        }
+        println!();
+    }
+
+    if summary.len() > 1 {
+        display_summary(&summary);
+    }
+
+    Ok(())
+}
+
+fn display_transfer(transfer: &ZoneTransfer) {
+    if transfer.rcode != ResponseCode::NoError {
+        println!(
+            "  {} Transfer refused: {}",
+            "🔒".bright_green(),
+            rcode_name(transfer.rcode).bright_yellow().bold()
+        );
+        return;
+    }
+
+    if transfer.records.is_empty() {
+        println!("  {} Server closed the transfer without sending any records", "🔒".bright_green());
+        return;
+    }
+
+    let serial = soa_serial(transfer).map(|s| s.to_string()).unwrap_or_else(|| "-".to_string());
+    if transfer.complete {
+        println!("  {} Transfer {}", "🔓".bright_yellow(), "ALLOWED".bright_yellow().bold());
+    } else {
+        println!(
+            "  {} Transfer {}: the server stopped before the closing SOA",
+            "🔓".bright_yellow(),
+            "INCOMPLETE".bright_red().bold()
+        );
+    }
+    println!(
+        "  Records: {}  SOA serial: {}  Messages: {}  Size: {}  Time: {}",
+        zone_records(transfer).len().to_string().bright_white(),
+        serial.bright_magenta(),
+        transfer.messages,
+        format_bytes(transfer.bytes as u64),
+        format_duration(transfer.elapsed)
+    );
+
+    let mut by_type: BTreeMap<String, usize> = BTreeMap::new();
+    for record in zone_records(transfer) {
+        *by_type.entry(record.record_type().to_string()).or_insert(0) += 1;
+    }
+    let types: Vec<String> = by_type.iter().map(|(rtype, count)| format!("{} {}", rtype, count)).collect();
+    println!("  Types: {}", types.join(", ").bright_cyan());
+}
+
+fn display_summary(summary: &[(String, Option<ZoneTransfer>)]) {
+    println!("{}", "📊 TRANSFER SUMMARY".bright_blue().bold());
+
+    let width = summary.iter().map(|(label, _)| label.len()).max().unwrap_or(8);
+    let mut serials = Vec::new();
+
+    for (label, transfer) in summary {
+        let status = match transfer {
+            Some(t) if t.rcode == ResponseCode::NoError && !t.records.is_empty() => {
+                let serial = soa_serial(t);
+                let state = if t.complete {
+                    serials.extend(serial);
+                    "ALLOWED".bright_yellow()
+                } else {
+                    "INCOMPLETE".bright_red()
+                };
+                format!(
+                    "{} {} records, serial {}",
+                    state,
+                    zone_records(t).len(),
+                    serial.map(|s| s.to_string()).unwrap_or_else(|| "-".to_string())
+                )
+            }
+            Some(t) if t.rcode != ResponseCode::NoError => format!("{} ({})", "REFUSED".bright_green(), rcode_name(t.rcode)),
+            Some(_) => "REFUSED".bright_green().to_string(),
+            None => "ERROR".bright_red().to_string(),
+        };
+        println!("  {:<width$}  {}", label, status, width = width);
+    }
+
+    serials.sort_unstable();
+    serials.dedup();
+    if serials.len() > 1 {
+        println!();
+        println!(
+            "{} Servers transferred different serials: {}",
+            "⚠️".yellow(),
+            serials.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(", ").bright_red()
+        );
+    }
+}
+
+fn soa_serial(transfer: &ZoneTransfer) -> Option<u32> {
+    transfer.records.first().and_then(|record| match record.data() {
+        Some(RData::SOA(soa)) => Some(soa.serial()),
+        _ => None,
+    })
+}
+
+/// Whether the transfer holds the whole zone. An IXFR answer is incremental
+/// when its second record is an SOA opening the first difference sequence
+/// (RFC 1995 section 4), and a lone SOA means nothing changed; anything
+/// else is the full zone, as servers fall back to when they lack history.
+fn is_full_zone(transfer: &ZoneTransfer) -> bool {
+    match transfer.records.get(1).and_then(|record| record.data()) {
+        None => false,
+        // An AXFR of a zone holding nothing but its SOA
+        Some(RData::SOA(_)) => transfer.records.len() == 2,
+        Some(_) => true,
+    }
+}
+
+/// The zone's records without the SOA that closes the transfer. An
+/// incomplete transfer has no closing SOA, so every record is kept.
+fn zone_records(transfer: &ZoneTransfer) -> &[trust_dns_resolver::proto::rr::Record] {
+    match transfer.records.len() {
+        len if len > 1 && transfer.complete => &transfer.records[..len - 1],
+        _ => &transfer.records,
+    }
+}
+
+fn save_zone_file(zone: &str, server: SocketAddr, transfer: &ZoneTransfer, path: &str) -> Result<usize> {
+    if !transfer.complete {
+        return Err(anyhow::anyhow!("the transfer is incomplete"));
+    }
+    let records = zone_records(transfer);
+
+    let mut contents = String::new();
+    contents.push_str(&format!("; Zone transfer of {} from {}\n", zone, server));
+    contents.push_str(&format!("; Generated by netdiag at {}\n", chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")));
+    contents.push_str(&format!("$ORIGIN {}.\n", zone.trim_end_matches('.')));
+
+    for record in records {
+        let data = record.data().map(format_rdata).unwrap_or_default();
+        contents.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\n",
+            record.name(),
+            record.ttl(),
+            record.dns_class(),
+            record.record_type(),
+            data
+        ));
+    }
+
+    std::fs::write(path, contents)?;
+    Ok(records.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trust_dns_resolver::proto::rr::rdata::SOA;
+    use trust_dns_resolver::proto::rr::{Name, Record};
+
+    fn transfer(records: Vec<Record>) -> ZoneTransfer {
+        ZoneTransfer {
+            rcode: ResponseCode::NoError,
+            records,
+            complete: true,
+            messages: 1,
+            bytes: 0,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    fn soa(serial: u32) -> Record {
+        let soa = SOA::new(Name::root(), Name::root(), serial, 3600, 600, 86400, 300);
+        Record::from_rdata(Name::from_ascii("example.test.").unwrap(), 300, RData::SOA(soa))
+    }
+
+    fn a(ip: &str) -> Record {
+        Record::from_rdata(Name::from_ascii("www.example.test.").unwrap(), 300, RData::A(ip.parse().unwrap()))
+    }
+
+    #[test]
+    fn test_is_full_zone() {
+        assert!(is_full_zone(&transfer(vec![soa(2), a("192.0.2.1"), soa(2)])));
+        assert!(is_full_zone(&transfer(vec![soa(2), soa(2)])));
+
+        // IXFR from serial 1: delete the old address, add the new one
+        let incremental = vec![soa(2), soa(1), a("192.0.2.1"), soa(2), a("192.0.2.2"), soa(2)];
+        assert!(!is_full_zone(&transfer(incremental)));
+        assert!(!is_full_zone(&transfer(vec![soa(2)])));
+    }
+
+    #[test]
+    fn test_zone_records_keeps_last_record_of_incomplete_transfer() {
+        let mut cut_off = transfer(vec![soa(2), a("192.0.2.1"), a("192.0.2.2")]);
+        cut_off.complete = false;
+        assert_eq!(zone_records(&cut_off).len(), 3);
+
+        let complete = transfer(vec![soa(2), a("192.0.2.1"), soa(2)]);
+        assert_eq!(zone_records(&complete).len(), 2);
+    }
+}
@@ -0,0 +1,337 @@
+use anyhow::Result;
+use colored::*;
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::Serialize;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Semaphore};
+
+use trust_dns_resolver::error::ResolveErrorKind;
+use trust_dns_resolver::proto::rr::RecordType;
+use trust_dns_resolver::TokioAsyncResolver;
+
+use super::{create_resolver, format_rdata, parse_record_type};
+use crate::network::dns::rcode_name;
+use crate::utils::exit::ExitStatus;
+
+/// Exit status when the options or the batch file are unusable, so a
+/// pipeline can tell a batch that never ran from one that did
+const EXIT_INVALID_INPUT: i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Csv,
+    Ndjson,
+}
+
+#[derive(Serialize)]
+struct BatchResult {
+    name: String,
+    #[serde(rename = "type")]
+    record_type: String,
+    rcode: String,
+    answers: Vec<String>,
+    /// Lowest TTL among the answers (or the negative-caching TTL)
+    ttl: Option<u32>,
+    latency_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+pub async fn dns_batch_command(
+    path: String,
+    server: Option<String>,
+    record_type: String,
+    concurrency: usize,
+    format: String,
+    output: Option<String>,
+    timeout: Duration,
+) -> Result<()> {
+    let format = match format.to_lowercase().as_str() {
+        "csv" => OutputFormat::Csv,
+        "ndjson" | "json" => OutputFormat::Ndjson,
+        other => {
+            eprintln!("{} Unsupported output format: {} (use csv or ndjson)", "❌".red(), other);
+            return Err(ExitStatus(EXIT_INVALID_INPUT).into());
+        }
+    };
+
+    let default_type = match parse_record_type(&record_type) {
+        Some(record_type) => record_type,
+        None => {
+            eprintln!("{} Unsupported record type: {}", "❌".red(), record_type);
+            return Err(ExitStatus(EXIT_INVALID_INPUT).into());
+        }
+    };
+
+    let queries = match std::fs::read_to_string(&path) {
+        Ok(contents) => match parse_batch(&contents, default_type) {
+            Ok(queries) => queries,
+            Err(e) => {
+                eprintln!("{} {}", "❌".red(), e);
+                return Err(ExitStatus(EXIT_INVALID_INPUT).into());
+            }
+        },
+        Err(e) => {
+            eprintln!("{} Failed to read batch file {}: {}", "❌".red(), path, e);
+            return Err(ExitStatus(EXIT_INVALID_INPUT).into());
+        }
+    };
+
+    // One resolver (and one cache) shared by every query
+    let resolver = match create_resolver(server.as_deref(), Some(timeout)) {
+        Ok(resolver) => Arc::new(resolver),
+        Err(e) => {
+            eprintln!("{} {}", "❌".red(), e);
+            return Err(ExitStatus(EXIT_INVALID_INPUT).into());
+        }
+    };
+
+    let mut writer: Box<dyn Write> = match output {
+        Some(ref path) => match std::fs::File::create(path) {
+            Ok(file) => Box::new(std::io::BufWriter::new(file)),
+            Err(e) => {
+                eprintln!("{} Failed to create {}: {}", "❌".red(), path, e);
+                return Err(ExitStatus(EXIT_INVALID_INPUT).into());
+            }
+        },
+        None => Box::new(std::io::stdout()),
+    };
+
+    let concurrency = concurrency.max(1);
+
+    // Results may go to stdout, so status messages go to stderr
+    eprintln!(
+        "{} Resolving {} queries from {} with concurrency {}",
+        "📋".bright_blue(),
+        queries.len().to_string().bright_white(),
+        path.bright_white(),
+        concurrency
+    );
+
+    let pb = ProgressBar::new(queries.len() as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({per_sec}) {msg}")
+            .unwrap()
+            .progress_chars("#>-"),
+    );
+
+    if format == OutputFormat::Csv {
+        writeln!(writer, "name,type,rcode,ttl,latency_ms,answers,error")?;
+    }
+
+    // Queries are spawned from a separate task and their handles queued in
+    // input order, so rows are written as soon as every earlier one is done
+    // rather than after the last name has been queued
+    let (sender, mut receiver) = mpsc::channel(concurrency);
+    let spawner = {
+        let pb = pb.clone();
+        tokio::spawn(async move {
+            let semaphore = Arc::new(Semaphore::new(concurrency));
+            for (name, record_type) in queries {
+                let Ok(permit) = semaphore.clone().acquire_owned().await else {
+                    break;
+                };
+                let resolver = resolver.clone();
+                let pb = pb.clone();
+                let query = (name.clone(), record_type);
+
+                let handle = tokio::spawn(async move {
+                    let _permit = permit;
+                    let result = resolve(&resolver, name, record_type).await;
+                    pb.inc(1);
+                    result
+                });
+                if sender.send((query, handle)).await.is_err() {
+                    break;
+                }
+            }
+        })
+    };
+
+    let mut rcodes: Vec<(String, usize)> = Vec::new();
+    while let Some(((name, record_type), handle)) = receiver.recv().await {
+        // A task that failed still gets a row, so the output lines up with the input
+        let result = match handle.await {
+            Ok(result) => result,
+            Err(e) => {
+                pb.inc(1);
+                failed_result(name, record_type, e.to_string())
+            }
+        };
+
+        match format {
+            OutputFormat::Csv => writeln!(writer, "{}", csv_line(&result))?,
+            OutputFormat::Ndjson => writeln!(writer, "{}", serde_json::to_string(&result)?)?,
+        }
+
+        match rcodes.iter_mut().find(|(rcode, _)| *rcode == result.rcode) {
+            Some((_, count)) => *count += 1,
+            None => rcodes.push((result.rcode, 1)),
+        }
+    }
+    writer.flush()?;
+    spawner.await?;
+
+    pb.finish_and_clear();
+
+    let summary: Vec<String> = rcodes.iter().map(|(rcode, count)| format!("{} {}", count, rcode)).collect();
+    eprintln!("{} {}", "📊".bright_blue(), summary.join(", "));
+    if let Some(ref path) = output {
+        eprintln!("{} Results written to {}", "💾".bright_green(), path.bright_white());
+    }
+
+    Ok(())
+}
+
+/// Reads `name [type]` lines, skipping blanks and `#` comments.
+fn parse_batch(contents: &str, default_type: RecordType) -> Result<Vec<(String, RecordType)>> {
+    let mut queries = Vec::new();
+
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let mut fields = line.split_whitespace();
+        let Some(name) = fields.next() else {
+            continue;
+        };
+
+        let record_type = match fields.next() {
+            Some(rtype) => parse_record_type(rtype)
+                .ok_or_else(|| anyhow::anyhow!("Line {}: unsupported record type {}", number + 1, rtype))?,
+            None => default_type,
+        };
+        queries.push((name.to_string(), record_type));
+    }
+
+    if queries.is_empty() {
+        return Err(anyhow::anyhow!("Batch file contains no names"));
+    }
+
+    Ok(queries)
+}
+
+async fn resolve(resolver: &TokioAsyncResolver, name: String, record_type: RecordType) -> BatchResult {
+    let start = Instant::now();
+    let result = resolver.lookup(name.as_str(), record_type).await;
+    let latency_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+    let mut batch_result = BatchResult {
+        name,
+        record_type: record_type.to_string(),
+        rcode: "NOERROR".to_string(),
+        answers: Vec::new(),
+        ttl: None,
+        latency_ms: (latency_ms * 1000.0).round() / 1000.0,
+        error: None,
+    };
+
+    match result {
+        Ok(lookup) => {
+            batch_result.answers = lookup.records().iter().filter_map(|r| r.data().map(format_rdata)).collect();
+            batch_result.ttl = lookup.records().iter().map(|record| record.ttl()).min();
+        }
+        Err(e) => match e.kind() {
+            ResolveErrorKind::NoRecordsFound { response_code, negative_ttl, .. } => {
+                batch_result.rcode = rcode_name(*response_code);
+                batch_result.ttl = *negative_ttl;
+            }
+            ResolveErrorKind::Timeout => {
+                batch_result.rcode = "TIMEOUT".to_string();
+                batch_result.error = Some(e.to_string());
+            }
+            _ => {
+                batch_result.rcode = "ERROR".to_string();
+                batch_result.error = Some(e.to_string());
+            }
+        },
+    }
+
+    batch_result
+}
+
+fn failed_result(name: String, record_type: RecordType, error: String) -> BatchResult {
+    BatchResult {
+        name,
+        record_type: record_type.to_string(),
+        rcode: "ERROR".to_string(),
+        answers: Vec::new(),
+        ttl: None,
+        latency_ms: 0.0,
+        error: Some(error),
+    }
+}
+
+fn csv_line(result: &BatchResult) -> String {
+    [
+        csv_field(&result.name),
+        csv_field(&result.record_type),
+        csv_field(&result.rcode),
+        result.ttl.map(|ttl| ttl.to_string()).unwrap_or_default(),
+        format!("{:.3}", result.latency_ms),
+        csv_field(&result.answers.join(";")),
+        csv_field(result.error.as_deref().unwrap_or("")),
+    ]
+    .join(",")
+}
+
+/// Quotes a field when it contains a separator, quote or newline (RFC 4180).
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_batch() {
+        let contents = "# audit list\nexample.com\nexample.com mx\n\nexample.org TXT  # mail\n";
+        let queries = parse_batch(contents, RecordType::A).unwrap();
+
+        assert_eq!(
+            queries,
+            vec![
+                ("example.com".to_string(), RecordType::A),
+                ("example.com".to_string(), RecordType::MX),
+                ("example.org".to_string(), RecordType::TXT),
+            ]
+        );
+        assert!(parse_batch("example.com BOGUS\n", RecordType::A).is_err());
+        assert!(parse_batch("# nothing\n", RecordType::A).is_err());
+    }
+
+    #[test]
+    fn test_csv_line() {
+        let result = BatchResult {
+            name: "example.com".to_string(),
+            record_type: "TXT".to_string(),
+            rcode: "NOERROR".to_string(),
+            answers: vec!["\"v=spf1 -all\"".to_string(), "\"a,b\"".to_string()],
+            ttl: Some(300),
+            latency_ms: 12.5,
+            error: None,
+        };
+
+        assert_eq!(
+            csv_line(&result),
+            "example.com,TXT,NOERROR,300,12.500,\"\"\"v=spf1 -all\"\";\"\"a,b\"\"\","
+        );
+    }
+
+    #[test]
+    fn test_failed_result() {
+        let result = failed_result("example.com".to_string(), RecordType::MX, "task panicked".to_string());
+
+        assert_eq!(csv_line(&result), "example.com,MX,ERROR,,0.000,,task panicked");
+        assert_eq!(
+            serde_json::to_string(&result).unwrap(),
+            r#"{"name":"example.com","type":"MX","rcode":"ERROR","answers":[],"ttl":null,"latency_ms":0.0,"error":"task panicked"}"#
+        );
+    }
+}
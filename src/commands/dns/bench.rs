@@ -0,0 +1,264 @@
+use anyhow::Result;
+use colored::*;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use trust_dns_resolver::proto::op::ResponseCode;
+use trust_dns_resolver::proto::rr::RecordType;
+
+use super::parse_record_type;
+use super::targets::{resolver_targets, target_label};
+use crate::network::dns::{send_query, DnsQuery, Transport};
+use crate::utils::format::format_duration;
+use crate::utils::stats::percentile;
+
+const DEFAULT_DOMAINS: &[&str] = &[
+    "google.com",
+    "amazon.com",
+    "wikipedia.org",
+    "github.com",
+    "cloudflare.com",
+    "microsoft.com",
+    "apple.com",
+    "netflix.com",
+];
+
+#[derive(Default)]
+struct BenchStats {
+    /// First query for each domain, which may or may not hit the cache
+    first: Vec<Duration>,
+    /// Repeated queries for a name the resolver has just answered
+    cached: Vec<Duration>,
+    /// Queries for random, never-seen names that force full recursion
+    cold: Vec<Duration>,
+    queries: u32,
+    failures: u32,
+}
+
+pub async fn dns_bench_command(
+    resolvers: Vec<String>,
+    resolvers_file: Option<String>,
+    domains: Vec<String>,
+    domains_file: Option<String>,
+    rounds: u32,
+    record_type: String,
+    timeout: Duration,
+) -> Result<()> {
+    println!("{}", "⏱️ DNS RESOLVER BENCHMARK".bright_green().bold());
+
+    let record_type = match parse_record_type(&record_type) {
+        Some(record_type) => record_type,
+        None => {
+            println!("{} Unsupported record type: {}", "❌".red(), record_type);
+            return Ok(());
+        }
+    };
+
+    let targets = match resolver_targets(&resolvers, resolvers_file.as_deref()) {
+        Ok(targets) => targets,
+        Err(e) => {
+            println!("{} {}", "❌".red(), e);
+            return Ok(());
+        }
+    };
+
+    let domains = match benchmark_domains(domains, domains_file.as_deref()) {
+        Ok(domains) => domains,
+        Err(e) => {
+            println!("{} {}", "❌".red(), e);
+            return Ok(());
+        }
+    };
+
+    if let Some(e) = domains.iter().find_map(|domain| DnsQuery::new(domain, record_type).err()) {
+        println!("{} {}", "❌".red(), e);
+        return Ok(());
+    }
+
+    println!(
+        "Benchmarking {} resolvers with {} domains ({} {} queries per domain)",
+        targets.len().to_string().bright_white(),
+        domains.len().to_string().bright_white(),
+        (rounds + 2).to_string().bright_white(),
+        format!("{:?}", record_type).bright_yellow()
+    );
+    println!();
+
+    let total = targets.len() as u64 * domains.len() as u64 * (rounds as u64 + 2);
+    let pb = ProgressBar::new(total);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}")
+            .unwrap()
+            .progress_chars("#>-"),
+    );
+
+    // Resolvers run in parallel, but each one sees its queries one at a time
+    // so that we don't measure our own load
+    let mut handles = Vec::new();
+    for target in &targets {
+        let server = target.server;
+        let domains = domains.clone();
+        let pb = pb.clone();
+        handles.push(tokio::spawn(async move {
+            bench_resolver(server, &domains, record_type, rounds, timeout, &pb).await
+        }));
+    }
+
+    let mut results = Vec::new();
+    for (target, handle) in targets.iter().zip(handles) {
+        let stats = handle.await.unwrap_or_default();
+        results.push((target_label(target), stats));
+    }
+
+    pb.finish_and_clear();
+
+    // Fastest (by cached median) first, resolvers that never answered last
+    results.sort_by_key(|(_, stats)| percentile(&stats.cached, 50.0).unwrap_or(Duration::MAX));
+    display_results(&results);
+
+    Ok(())
+}
+
+fn benchmark_domains(domains: Vec<String>, domains_file: Option<&str>) -> Result<Vec<String>> {
+    let mut all_domains = domains;
+
+    if let Some(path) = domains_file {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read domains file {}: {}", path, e))?;
+        for line in contents.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if !line.is_empty() {
+                all_domains.push(line.to_string());
+            }
+        }
+    }
+
+    if all_domains.is_empty() {
+        all_domains = DEFAULT_DOMAINS.iter().map(|d| d.to_string()).collect();
+    }
+
+    Ok(all_domains)
+}
+
+async fn bench_resolver(
+    server: SocketAddr,
+    domains: &[String],
+    record_type: RecordType,
+    rounds: u32,
+    timeout: Duration,
+    pb: &ProgressBar,
+) -> BenchStats {
+    let mut stats = BenchStats::default();
+
+    for domain in domains {
+        if let Some(elapsed) = timed_query(server, domain, record_type, timeout, &mut stats).await {
+            stats.first.push(elapsed);
+        }
+        pb.inc(1);
+
+        for _ in 0..rounds {
+            if let Some(elapsed) = timed_query(server, domain, record_type, timeout, &mut stats).await {
+                stats.cached.push(elapsed);
+            }
+            pb.inc(1);
+        }
+
+        // A random label under the domain can't be cached anywhere
+        let cold_name = format!("nd-{:08x}.{}", rand::random::<u32>(), domain);
+        if let Some(elapsed) = timed_query(server, &cold_name, record_type, timeout, &mut stats).await {
+            stats.cold.push(elapsed);
+        }
+        pb.inc(1);
+    }
+
+    stats
+}
+
+/// Sends one query and returns its latency, or `None` (counted as a failure)
+/// if the name is invalid, it timed out or the resolver answered with an
+/// error rcode.
+async fn timed_query(
+    server: SocketAddr,
+    name: &str,
+    record_type: RecordType,
+    timeout: Duration,
+    stats: &mut BenchStats,
+) -> Option<Duration> {
+    stats.queries += 1;
+
+    // A cold name can outgrow the 255-byte limit even when the domain fits
+    let Ok(query) = DnsQuery::new(name, record_type) else {
+        stats.failures += 1;
+        return None;
+    };
+    match send_query(server, &query, Transport::Udp, timeout).await {
+        Ok(response)
+            if matches!(
+                response.message.response_code(),
+                ResponseCode::NoError | ResponseCode::NXDomain
+            ) =>
+        {
+            Some(response.elapsed)
+        }
+        _ => {
+            stats.failures += 1;
+            None
+        }
+    }
+}
+
+fn display_results(results: &[(String, BenchStats)]) {
+    let label_width = results.iter().map(|(label, _)| label.len()).max().unwrap_or(8).max(8);
+    let show = |sample: Option<Duration>| sample.map(format_duration).unwrap_or_else(|| "-".to_string());
+
+    println!("{}", "📊 BENCHMARK RESULTS".bright_blue().bold());
+    println!(
+        "{}",
+        format!(
+            "  {:<width$}  {:>7} {:>7}  {:>10} {:>10} {:>10}  {:>10} {:>10}",
+            "Resolver", "Queries", "Failed", "p50", "p95", "max", "First p50", "Cold p50",
+            width = label_width
+        )
+        .bright_blue()
+    );
+
+    for (label, stats) in results {
+        let failure_rate = if stats.queries > 0 {
+            stats.failures as f64 / stats.queries as f64 * 100.0
+        } else {
+            0.0
+        };
+        let failed = format!("{:.1}%", failure_rate);
+        let failed = if stats.failures == 0 { failed.bright_green() } else { failed.bright_red() };
+
+        println!(
+            "  {:<width$}  {:>7} {:>7}  {:>10} {:>10} {:>10}  {:>10} {:>10}",
+            label.bright_white(),
+            stats.queries,
+            failed,
+            show(percentile(&stats.cached, 50.0)).bright_green(),
+            show(percentile(&stats.cached, 95.0)).bright_yellow(),
+            show(percentile(&stats.cached, 100.0)).bright_red(),
+            show(percentile(&stats.first, 50.0)).bright_cyan(),
+            show(percentile(&stats.cold, 50.0)).bright_magenta(),
+            width = label_width
+        );
+    }
+
+    println!();
+    println!("{} p50/p95/max are measured on repeated (cached) queries", "ℹ️".blue());
+    println!("  • First: the first query for each domain (may already be cached)");
+    println!("  • Cold: random subdomains that force the resolver to recurse");
+
+    if let Some((label, stats)) = results.iter().find(|(_, stats)| !stats.cached.is_empty()) {
+        println!();
+        println!(
+            "{} Fastest resolver: {} (p50 {})",
+            "🏆".bright_yellow(),
+            label.bright_white().bold(),
+            show(percentile(&stats.cached, 50.0)).bright_green()
+        );
+    }
+}
@@ -0,0 +1,286 @@
+use anyhow::Result;
+use colored::*;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use trust_dns_resolver::proto::op::ResponseCode;
+use trust_dns_resolver::proto::rr::{RData, RecordType};
+
+use super::targets::{authoritative_targets, resolver_targets, target_label, Target};
+use super::{format_rdata, parse_record_type};
+use crate::network::dns::{rcode_name, send_query, DnsQuery, Transport};
+use crate::utils::format::format_duration;
+
+struct Answer {
+    rcode: ResponseCode,
+    records: Vec<String>,
+    min_ttl: Option<u32>,
+    serial: Option<u32>,
+    elapsed: Duration,
+}
+
+pub async fn dns_compare_command(
+    domain: String,
+    server: Option<String>,
+    record_type: String,
+    resolvers: Vec<String>,
+    resolvers_file: Option<String>,
+    authoritative: bool,
+    timeout: Duration,
+) -> Result<()> {
+    println!("{} {}", "🌐 DNS COMPARE".bright_green().bold(), domain.bright_white().bold());
+
+    let record_type = match parse_record_type(&record_type) {
+        Some(record_type) => record_type,
+        None => {
+            println!("{} Unsupported record type: {}", "❌".red(), record_type);
+            return Ok(());
+        }
+    };
+
+    let (targets, zone) = if authoritative {
+        match authoritative_targets(&domain, server.as_deref(), timeout).await {
+            Ok((zone, targets)) => (targets, Some(zone)),
+            Err(e) => {
+                println!("{} Failed to find authoritative servers: {}", "❌".red(), e);
+                return Ok(());
+            }
+        }
+    } else {
+        match resolver_targets(&resolvers, resolvers_file.as_deref()) {
+            Ok(targets) => (targets, None),
+            Err(e) => {
+                println!("{} {}", "❌".red(), e);
+                return Ok(());
+            }
+        }
+    };
+
+    if targets.is_empty() {
+        println!("{} No resolvers to compare", "❌".red());
+        return Ok(());
+    }
+
+    println!("Query: {} {}", domain.bright_cyan(), format!("{:?}", record_type).bright_yellow());
+    if let Some(ref zone) = zone {
+        println!("Zone: {} ({} authoritative servers)", zone.bright_cyan(), targets.len());
+    } else {
+        println!("Resolvers: {}", targets.len());
+    }
+    println!();
+
+    // Send every query at once so all resolvers are sampled at the same moment
+    let mut handles = Vec::new();
+    for target in &targets {
+        let domain = domain.clone();
+        let zone = zone.clone();
+        let server = target.server;
+        handles.push(tokio::spawn(async move {
+            query_target(server, &domain, record_type, zone.as_deref(), timeout).await
+        }));
+    }
+
+    let mut results = Vec::new();
+    for handle in handles {
+        results.push(match handle.await {
+            Ok(result) => result,
+            Err(e) => Err(anyhow::anyhow!("Query task failed: {}", e)),
+        });
+    }
+
+    let consensus = majority_answer(&results);
+    display_matrix(&targets, &results, consensus.as_ref(), zone.is_some());
+
+    // Summary
+    println!();
+    let disagreeing = results
+        .iter()
+        .filter(|result| match result {
+            Ok(answer) => Some(answer_key(answer)) != consensus,
+            Err(_) => true,
+        })
+        .count();
+
+    if disagreeing == 0 {
+        println!("{} All {} servers agree", "✅".green(), results.len());
+    } else {
+        println!(
+            "{} {} of {} servers disagree with the majority answer",
+            "⚠️".yellow(),
+            disagreeing.to_string().bright_red().bold(),
+            results.len()
+        );
+    }
+
+    if zone.is_some() {
+        let mut serials: Vec<u32> = results
+            .iter()
+            .filter_map(|result| result.as_ref().ok().and_then(|answer| answer.serial))
+            .collect();
+        serials.sort_unstable();
+        serials.dedup();
+
+        match serials.len() {
+            0 => println!("{} No SOA serials could be retrieved", "❌".red()),
+            1 => println!("{} All servers report SOA serial {}", "✅".green(), serials[0].to_string().bright_magenta()),
+            _ => println!(
+                "{} SOA serials differ between servers: {}",
+                "⚠️".yellow(),
+                serials.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(", ").bright_red()
+            ),
+        }
+    }
+
+    Ok(())
+}
+
+async fn query_target(
+    server: SocketAddr,
+    domain: &str,
+    record_type: RecordType,
+    zone: Option<&str>,
+    timeout: Duration,
+) -> Result<Answer> {
+    // Authoritative servers are asked without recursion so they answer from their own data
+    let query = DnsQuery::new(domain, record_type)?.recursion_desired(zone.is_none());
+    let mut response = send_query(server, &query, Transport::Udp, timeout).await?;
+    if response.message.truncated() {
+        response = send_query(server, &query, Transport::Tcp, timeout).await?;
+    }
+
+    let mut records: Vec<String> = response
+        .message
+        .answers()
+        .iter()
+        .filter_map(|record| record.data().map(format_rdata))
+        .collect();
+    records.sort();
+
+    let min_ttl = response.message.answers().iter().map(|record| record.ttl()).min();
+
+    let serial = match zone {
+        Some(zone) => {
+            let soa_query = DnsQuery::new(zone, RecordType::SOA)?.recursion_desired(false);
+            send_query(server, &soa_query, Transport::Udp, timeout)
+                .await
+                .ok()
+                .and_then(|soa| {
+                    soa.message.answers().iter().find_map(|record| match record.data() {
+                        Some(RData::SOA(soa)) => Some(soa.serial()),
+                        _ => None,
+                    })
+                })
+        }
+        None => None,
+    };
+
+    Ok(Answer {
+        rcode: response.message.response_code(),
+        records,
+        min_ttl,
+        serial,
+        elapsed: response.elapsed,
+    })
+}
+
+fn answer_key(answer: &Answer) -> (ResponseCode, Vec<String>) {
+    (answer.rcode, answer.records.clone())
+}
+
+/// Picks the most common (rcode, answer set) pair among the successful queries.
+fn majority_answer(results: &[Result<Answer>]) -> Option<(ResponseCode, Vec<String>)> {
+    let mut counts: HashMap<(ResponseCode, Vec<String>), usize> = HashMap::new();
+    for answer in results.iter().flatten() {
+        *counts.entry(answer_key(answer)).or_insert(0) += 1;
+    }
+
+    counts
+        .into_iter()
+        .max_by(|(key_a, count_a), (key_b, count_b)| count_a.cmp(count_b).then_with(|| key_b.1.cmp(&key_a.1)))
+        .map(|(key, _)| key)
+}
+
+fn display_matrix(
+    targets: &[Target],
+    results: &[Result<Answer>],
+    consensus: Option<&(ResponseCode, Vec<String>)>,
+    show_serial: bool,
+) {
+    let label_width = targets
+        .iter()
+        .map(|target| target_label(target).len())
+        .max()
+        .unwrap_or(8)
+        .max(8);
+
+    let header = if show_serial {
+        format!("  {:<width$}  {:<9} {:>7} {:>10} {:>11}  Answers", "Server", "RCODE", "TTL", "Time", "Serial", width = label_width)
+    } else {
+        format!("  {:<width$}  {:<9} {:>7} {:>10}  Answers", "Server", "RCODE", "TTL", "Time", width = label_width)
+    };
+    println!("{}", header.bright_blue().bold());
+
+    for (target, result) in targets.iter().zip(results) {
+        let label = format!("{:<width$}", target_label(target), width = label_width);
+
+        match result {
+            Ok(answer) => {
+                let agrees = consensus == Some(&answer_key(answer));
+                let marker = if agrees { "=".bright_green() } else { "≠".bright_red().bold() };
+                let rcode = format!("{:<9}", rcode_name(answer.rcode));
+                let rcode = if answer.rcode == ResponseCode::NoError { rcode.bright_green() } else { rcode.bright_red() };
+                let ttl = answer.min_ttl.map(|ttl| ttl.to_string()).unwrap_or_else(|| "-".to_string());
+                let serial = answer.serial.map(|serial| serial.to_string()).unwrap_or_else(|| "-".to_string());
+
+                let first = answer.records.first().cloned().unwrap_or_else(|| "(empty)".to_string());
+                let first = if agrees { first.bright_white() } else { first.bright_red() };
+
+                if show_serial {
+                    println!("{} {}  {} {:>7} {:>10} {:>11}  {}", marker, label, rcode, ttl.bright_cyan(), format_duration(answer.elapsed), serial.bright_magenta(), first);
+                } else {
+                    println!("{} {}  {} {:>7} {:>10}  {}", marker, label, rcode, ttl.bright_cyan(), format_duration(answer.elapsed), first);
+                }
+
+                let indent = label_width + if show_serial { 46 } else { 34 };
+                for record in answer.records.iter().skip(1) {
+                    let record = if agrees { record.bright_white() } else { record.bright_red() };
+                    println!("{:indent$}{}", "", record, indent = indent);
+                }
+            }
+            Err(e) => {
+                println!("{} {}  {}", "✗".bright_red().bold(), label, e.to_string().red());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_majority_answer() {
+        let answer = |records: &[&str]| -> Result<Answer> {
+            Ok(Answer {
+                rcode: ResponseCode::NoError,
+                records: records.iter().map(|r| r.to_string()).collect(),
+                min_ttl: Some(300),
+                serial: None,
+                elapsed: Duration::from_millis(10),
+            })
+        };
+
+        let results = vec![
+            answer(&["192.0.2.1"]),
+            answer(&["192.0.2.2"]),
+            answer(&["192.0.2.2"]),
+            Err(anyhow::anyhow!("timeout")),
+        ];
+
+        assert_eq!(
+            majority_answer(&results),
+            Some((ResponseCode::NoError, vec!["192.0.2.2".to_string()]))
+        );
+    }
+}
@@ -0,0 +1,395 @@
+use anyhow::Result;
+use colored::*;
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use trust_dns_resolver::error::ResolveErrorKind;
+use trust_dns_resolver::TokioAsyncResolver;
+
+use super::create_resolver;
+use crate::network::dns::reverse_name;
+use crate::utils::format::format_duration;
+
+/// IP-based blocklists checked when no zones are given.
+const DEFAULT_IP_ZONES: &[&str] = &[
+    "zen.spamhaus.org",
+    "bl.spamcop.net",
+    "b.barracudacentral.org",
+    "dnsbl.sorbs.net",
+    "psbl.surriel.com",
+    "bl.mailspike.net",
+    "dnsbl-1.uceprotect.net",
+    "ix.dnsbl.manitu.net",
+];
+
+/// Domain-based (RHSBL) blocklists checked when no zones are given.
+const DEFAULT_DOMAIN_ZONES: &[&str] = &["dbl.spamhaus.org", "multi.surbl.org", "multi.uribl.com"];
+
+enum Listing {
+    /// The list has an entry: 127.0.0.x codes and any TXT reasons
+    Listed { codes: Vec<Ipv4Addr>, reasons: Vec<String> },
+    NotListed,
+    /// The list answered, but with a code meaning it would not answer this query
+    Refused(Ipv4Addr),
+    Error(String),
+}
+
+struct ZoneResult {
+    zone: String,
+    listing: Listing,
+    elapsed: Duration,
+}
+
+pub async fn dns_dnsbl_command(
+    target: String,
+    server: Option<String>,
+    zones: Vec<String>,
+    zones_file: Option<String>,
+    timeout: Duration,
+) -> Result<()> {
+    println!("{} {}", "🚫 DNSBL CHECK".bright_green().bold(), target.bright_white().bold());
+
+    let target = target.trim().trim_end_matches('.').to_string();
+    let is_ip = target.parse::<IpAddr>().is_ok();
+
+    let mut zones: Vec<String> = zones.iter().map(|zone| zone.trim().trim_matches('.').to_string()).collect();
+    if let Some(ref path) = zones_file {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => zones.extend(parse_zones_file(&contents)),
+            Err(e) => {
+                println!("{} Failed to read zones file {}: {}", "❌".red(), path, e);
+                return Ok(());
+            }
+        }
+    }
+    if zones.is_empty() {
+        let defaults = if is_ip { DEFAULT_IP_ZONES } else { DEFAULT_DOMAIN_ZONES };
+        zones = defaults.iter().map(|zone| zone.to_string()).collect();
+    }
+
+    let resolver = match create_resolver(server.as_deref(), Some(timeout)) {
+        Ok(resolver) => Arc::new(resolver),
+        Err(e) => {
+            println!("{} {}", "❌".red(), e);
+            return Ok(());
+        }
+    };
+
+    println!(
+        "Target: {} ({})",
+        target.bright_cyan(),
+        if is_ip { "IP blocklists" } else { "domain blocklists (RHSBL)" }
+    );
+    if let Some(ref server_ip) = server {
+        println!("Using DNS server: {}", server_ip.bright_magenta());
+    }
+    println!("Lists: {}", zones.len());
+    println!();
+
+    // Every list is independent, so they are all asked at once
+    let mut handles = Vec::new();
+    for zone in &zones {
+        let resolver = resolver.clone();
+        let name = query_name(&target, zone);
+        let zone = zone.clone();
+        handles.push(tokio::spawn(async move {
+            let start_time = Instant::now();
+            let listing = check_zone(&resolver, &zone, &name).await;
+            ZoneResult {
+                zone,
+                listing,
+                elapsed: start_time.elapsed(),
+            }
+        }));
+    }
+
+    let mut results = Vec::new();
+    for handle in handles {
+        if let Ok(result) = handle.await {
+            results.push(result);
+        }
+    }
+
+    display_results(&results);
+    display_summary(&target, &results);
+
+    Ok(())
+}
+
+/// Zones one per line, skipping blank lines and `#` comments.
+fn parse_zones_file(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim().trim_matches('.'))
+        .filter(|zone| !zone.is_empty())
+        .map(|zone| zone.to_string())
+        .collect()
+}
+
+/// `4.3.2.1.zone.` for 1.2.3.4, nibbles for IPv6, `domain.zone.` for RHSBLs.
+fn query_name(target: &str, zone: &str) -> String {
+    match target.parse::<IpAddr>() {
+        Ok(ip) => {
+            let reversed = reverse_name(ip);
+            let labels = reversed
+                .trim_end_matches("in-addr.arpa.")
+                .trim_end_matches("ip6.arpa.");
+            format!("{}{}.", labels, zone)
+        }
+        Err(_) => format!("{}.{}.", target, zone),
+    }
+}
+
+async fn check_zone(resolver: &TokioAsyncResolver, zone: &str, name: &str) -> Listing {
+    let codes: Vec<Ipv4Addr> = match resolver.ipv4_lookup(name).await {
+        Ok(lookup) => lookup.iter().map(|a| a.0).collect(),
+        Err(e) => {
+            return match e.kind() {
+                ResolveErrorKind::NoRecordsFound { .. } => Listing::NotListed,
+                _ => Listing::Error(e.to_string()),
+            };
+        }
+    };
+
+    if let Some(code) = codes.iter().find(|code| is_refusal(zone, code)) {
+        return Listing::Refused(*code);
+    }
+    if let Some(code) = codes.iter().find(|code| code.octets()[0] != 127) {
+        // Listings are always in 127/8; anything else is a rewritten NXDOMAIN
+        return Listing::Error(format!("unexpected answer {} (resolver rewrites NXDOMAIN?)", code));
+    }
+
+    let reasons = match resolver.txt_lookup(name).await {
+        Ok(lookup) => lookup
+            .iter()
+            .map(|txt| {
+                txt.iter()
+                    .map(|data| String::from_utf8_lossy(data).to_string())
+                    .collect::<String>()
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+
+    Listing::Listed { codes, reasons }
+}
+
+/// Whether `zone` is `suffix` or lies below it, on a label boundary.
+fn in_zone(zone: &str, suffix: &str) -> bool {
+    let zone = zone.trim_end_matches('.');
+    zone == suffix || zone.ends_with(&format!(".{}", suffix))
+}
+
+/// Codes lists return to say "not answering you" rather than "listed":
+/// Spamhaus uses 127.255.255.x, URIBL and SURBL use 127.0.0.1. Other lists
+/// may use 127.0.0.1 as a listing, so it only means a refusal for those two.
+fn is_refusal(zone: &str, code: &Ipv4Addr) -> bool {
+    let zone = zone.to_ascii_lowercase();
+    let blocks_with_loopback = in_zone(&zone, "uribl.com") || in_zone(&zone, "surbl.org");
+
+    code.octets()[..3] == [127, 255, 255] || (blocks_with_loopback && *code == Ipv4Addr::new(127, 0, 0, 1))
+}
+
+fn describe_refusal(code: &Ipv4Addr) -> &'static str {
+    match code.octets() {
+        [127, 255, 255, 252] => "typo in the DNSBL name",
+        [127, 255, 255, 254] => "queries through public/open resolvers are refused",
+        [127, 255, 255, 255] => "query volume limit exceeded",
+        [127, 0, 0, 1] => "query blocked (public resolver or volume limit)",
+        _ => "query refused",
+    }
+}
+
+/// Explains a return code using the list's published meaning where known.
+fn describe_code(zone: &str, code: &Ipv4Addr) -> String {
+    let zone = zone.to_ascii_lowercase();
+    let [_, _, third, last] = code.octets();
+
+    if in_zone(&zone, "dbl.spamhaus.org") && third == 1 {
+        let meaning = match last {
+            2 => "spam domain",
+            4 => "phishing domain",
+            5 => "malware domain",
+            6 => "botnet C&C domain",
+            102 => "abused legit spam",
+            103 => "abused spammed redirector",
+            104 => "abused legit phish",
+            105 => "abused legit malware",
+            106 => "abused legit botnet C&C",
+            _ => "listed",
+        };
+        return meaning.to_string();
+    }
+
+    if in_zone(&zone, "spamhaus.org") {
+        let meaning = match last {
+            2 => "SBL: spam source",
+            3 => "SBL CSS: snowshoe spam",
+            4..=7 => "XBL: exploited host / botnet",
+            9 => "SBL DROP: hijacked netblock",
+            10 => "PBL: ISP dynamic/end-user range",
+            11 => "PBL: Spamhaus-maintained end-user range",
+            _ => "listed",
+        };
+        return meaning.to_string();
+    }
+
+    // SURBL and URIBL encode several lists as a bitmask in the last octet
+    let bits: &[(u8, &str)] = if in_zone(&zone, "surbl.org") {
+        &[(8, "phishing"), (16, "malware"), (64, "abuse/spam"), (128, "cracked site")]
+    } else if in_zone(&zone, "uribl.com") {
+        &[(2, "black"), (4, "grey"), (8, "red")]
+    } else {
+        &[]
+    };
+    let matched: Vec<&str> = bits.iter().filter(|(bit, _)| last & bit != 0).map(|(_, name)| *name).collect();
+    if !matched.is_empty() {
+        return matched.join(", ");
+    }
+
+    if last == 2 {
+        "listed".to_string()
+    } else {
+        format!("listed (code {})", last)
+    }
+}
+
+fn display_results(results: &[ZoneResult]) {
+    let width = results.iter().map(|result| result.zone.len()).max().unwrap_or(4).max(4);
+
+    println!(
+        "{}",
+        format!("  {:<width$}  {:<12} {:>10}  Details", "List", "Result", "Time", width = width)
+            .bright_blue()
+            .bold()
+    );
+
+    for result in results {
+        let zone = format!("{:<width$}", result.zone, width = width);
+        let time = format_duration(result.elapsed);
+
+        match result.listing {
+            Listing::Listed { ref codes, ref reasons } => {
+                let details: Vec<String> = codes
+                    .iter()
+                    .map(|code| format!("{} {}", code, describe_code(&result.zone, code)))
+                    .collect();
+                println!(
+                    "  {}  {:<12} {:>10}  {}",
+                    zone.bright_white(),
+                    "LISTED".bright_red().bold(),
+                    time,
+                    details.join("; ").bright_red()
+                );
+                for reason in reasons {
+                    println!("{:indent$}{}", "", reason.bright_white(), indent = width + 29);
+                }
+            }
+            Listing::NotListed => {
+                println!("  {}  {:<12} {:>10}", zone, "not listed".bright_green(), time);
+            }
+            Listing::Refused(ref code) => {
+                println!(
+                    "  {}  {:<12} {:>10}  {} {}",
+                    zone,
+                    "REFUSED".bright_yellow(),
+                    time,
+                    code,
+                    describe_refusal(code).bright_yellow()
+                );
+            }
+            Listing::Error(ref e) => {
+                println!("  {}  {:<12} {:>10}  {}", zone, "ERROR".bright_yellow(), time, e.red());
+            }
+        }
+    }
+}
+
+fn display_summary(target: &str, results: &[ZoneResult]) {
+    let listed: Vec<&str> = results
+        .iter()
+        .filter(|result| matches!(result.listing, Listing::Listed { .. }))
+        .map(|result| result.zone.as_str())
+        .collect();
+    let unchecked = results
+        .iter()
+        .filter(|result| matches!(result.listing, Listing::Refused(_) | Listing::Error(_)))
+        .count();
+
+    println!();
+    println!("{}", "📊 Summary".bright_blue().bold());
+
+    if listed.is_empty() {
+        println!(
+            "  {} {} is not listed on any of the {} lists that answered",
+            "✅".green(),
+            target.bright_white(),
+            results.len() - unchecked
+        );
+    } else {
+        println!(
+            "  {} {} is listed on {} of {} lists: {}",
+            "❌".red(),
+            target.bright_white(),
+            listed.len().to_string().bright_red().bold(),
+            results.len(),
+            listed.join(", ").bright_red()
+        );
+    }
+
+    if unchecked > 0 {
+        println!("  {} {} lists could not be checked", "⚠️".yellow(), unchecked);
+        if results.iter().any(|result| matches!(result.listing, Listing::Refused(_))) {
+            println!("  • Some lists refuse queries from public resolvers; use -s with your own recursive resolver");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_name() {
+        assert_eq!(query_name("192.0.2.10", "zen.spamhaus.org"), "10.2.0.192.zen.spamhaus.org.");
+        assert_eq!(
+            query_name("2001:db8::1", "zen.spamhaus.org"),
+            "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.zen.spamhaus.org."
+        );
+        assert_eq!(query_name("example.com", "dbl.spamhaus.org"), "example.com.dbl.spamhaus.org.");
+    }
+
+    #[test]
+    fn test_describe_code() {
+        let code = |last: u8| Ipv4Addr::new(127, 0, 0, last);
+
+        assert_eq!(describe_code("zen.spamhaus.org", &code(4)), "XBL: exploited host / botnet");
+        assert_eq!(describe_code("dbl.spamhaus.org", &Ipv4Addr::new(127, 0, 1, 4)), "phishing domain");
+        assert_eq!(describe_code("multi.surbl.org", &code(24)), "phishing, malware");
+        assert_eq!(describe_code("multi.uribl.com", &code(2)), "black");
+        assert_eq!(describe_code("bl.spamcop.net", &code(2)), "listed");
+        assert_eq!(describe_code("bl.example.net", &code(5)), "listed (code 5)");
+        assert_eq!(describe_code("multi.myuribl.com", &code(4)), "listed (code 4)");
+    }
+
+    #[test]
+    fn test_is_refusal() {
+        assert!(is_refusal("zen.spamhaus.org", &Ipv4Addr::new(127, 255, 255, 254)));
+        assert!(is_refusal("bl.example.net", &Ipv4Addr::new(127, 255, 255, 255)));
+        assert!(is_refusal("multi.uribl.com", &Ipv4Addr::new(127, 0, 0, 1)));
+        assert!(is_refusal("multi.surbl.org", &Ipv4Addr::new(127, 0, 0, 1)));
+        assert!(!is_refusal("multi.uribl.com", &Ipv4Addr::new(127, 0, 0, 2)));
+        // A list of its own may answer 127.0.0.1 for a listing
+        assert!(!is_refusal("bl.example.net", &Ipv4Addr::new(127, 0, 0, 1)));
+        // Look-alike zones are not URIBL or SURBL
+        assert!(!is_refusal("myuribl.com", &Ipv4Addr::new(127, 0, 0, 1)));
+        assert!(!is_refusal("bl.notsurbl.org", &Ipv4Addr::new(127, 0, 0, 1)));
+    }
+
+    #[test]
+    fn test_parse_zones_file() {
+        let zones = parse_zones_file("# lists\nzen.spamhaus.org\n\nbl.spamcop.net.  # trailing dot\n");
+        assert_eq!(zones, vec!["zen.spamhaus.org", "bl.spamcop.net"]);
+    }
+}
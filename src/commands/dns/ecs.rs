@@ -0,0 +1,332 @@
+use anyhow::Result;
+use colored::*;
+use ipnet::IpNet;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+use trust_dns_resolver::proto::op::{Message, ResponseCode};
+use trust_dns_resolver::proto::rr::rdata::opt::EdnsOption;
+use trust_dns_resolver::proto::rr::RecordType;
+
+use super::message::EDNS_CLIENT_SUBNET;
+use super::query::{client_subnet_option, parse_subnet};
+use super::{format_rdata, parse_record_type, raw_query_server};
+use crate::network::dns::{rcode_name, send_query, DnsQuery, Transport};
+use crate::utils::format::format_duration;
+
+struct Subnet {
+    net: IpNet,
+    label: Option<String>,
+}
+
+impl Subnet {
+    fn display(&self) -> String {
+        match self.label {
+            Some(ref label) => format!("{} {}", self.net, label),
+            None => self.net.to_string(),
+        }
+    }
+}
+
+struct Answer {
+    rcode: ResponseCode,
+    records: Vec<String>,
+    min_ttl: Option<u32>,
+    /// Scope prefix length the server returned, `None` if it sent no ECS option back
+    scope: Option<u8>,
+    elapsed: Duration,
+}
+
+pub async fn dns_ecs_command(
+    domain: String,
+    server: Option<String>,
+    record_type: String,
+    subnets: Vec<String>,
+    subnets_file: Option<String>,
+    concurrency: usize,
+    timeout: Duration,
+) -> Result<()> {
+    println!("{} {}", "🌍 EDNS CLIENT SUBNET".bright_green().bold(), domain.bright_white().bold());
+
+    let record_type = match parse_record_type(&record_type) {
+        Some(record_type) => record_type,
+        None => {
+            println!("{} Unsupported record type: {}", "❌".red(), record_type);
+            return Ok(());
+        }
+    };
+
+    let subnets = match load_subnets(&subnets, subnets_file.as_deref()) {
+        Ok(subnets) if !subnets.is_empty() => subnets,
+        Ok(_) => {
+            println!("{} No client subnets given", "❌".red());
+            return Ok(());
+        }
+        Err(e) => {
+            println!("{} {}", "❌".red(), e);
+            return Ok(());
+        }
+    };
+
+    let server_addr = match raw_query_server(server.as_deref()) {
+        Ok(addr) => addr,
+        Err(e) => {
+            println!("{} {}", "❌".red(), e);
+            return Ok(());
+        }
+    };
+
+    println!("Query: {} {}", domain.bright_cyan(), format!("{:?}", record_type).bright_yellow());
+    println!("Server: {}", server_addr.to_string().bright_magenta());
+    println!("Subnets: {}", subnets.len());
+    println!();
+
+    // A sweep sends every query to the one server, so keep it polite
+    let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+    let mut handles = Vec::new();
+    for subnet in &subnets {
+        let permit = semaphore.clone().acquire_owned().await?;
+        let domain = domain.clone();
+        let net = subnet.net;
+        handles.push(tokio::spawn(async move {
+            let _permit = permit;
+            query_subnet(server_addr, &domain, record_type, &net, timeout).await
+        }));
+    }
+
+    let mut results = Vec::new();
+    for handle in handles {
+        results.push(match handle.await {
+            Ok(result) => result,
+            Err(e) => Err(anyhow::anyhow!("Query task failed: {}", e)),
+        });
+    }
+
+    display_table(&subnets, &results);
+    if subnets.len() > 1 {
+        display_groups(&subnets, &results);
+    }
+    display_verdict(&results);
+
+    Ok(())
+}
+
+/// Collects subnets from the command line and a file of `subnet [label]` lines.
+fn load_subnets(subnets: &[String], subnets_file: Option<&str>) -> Result<Vec<Subnet>> {
+    let mut loaded = Vec::new();
+
+    for subnet in subnets {
+        loaded.push(Subnet {
+            net: parse_subnet(subnet)?,
+            label: None,
+        });
+    }
+
+    if let Some(path) = subnets_file {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read subnets file {}: {}", path, e))?;
+        loaded.extend(parse_subnets_file(&contents)?);
+    }
+
+    Ok(loaded)
+}
+
+fn parse_subnets_file(contents: &str) -> Result<Vec<Subnet>> {
+    let mut subnets = Vec::new();
+
+    for line in contents.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let mut parts = line.split_whitespace();
+        let Some(subnet) = parts.next() else {
+            continue;
+        };
+        let label = parts.collect::<Vec<_>>().join(" ");
+
+        subnets.push(Subnet {
+            net: parse_subnet(subnet)?,
+            label: if label.is_empty() { None } else { Some(label) },
+        });
+    }
+
+    Ok(subnets)
+}
+
+async fn query_subnet(
+    server: SocketAddr,
+    domain: &str,
+    record_type: RecordType,
+    subnet: &IpNet,
+    timeout: Duration,
+) -> Result<Answer> {
+    let query = DnsQuery::new(domain, record_type)?.edns_option(EDNS_CLIENT_SUBNET, client_subnet_option(subnet));
+    let mut response = send_query(server, &query, Transport::Udp, timeout).await?;
+    if response.message.truncated() {
+        response = send_query(server, &query, Transport::Tcp, timeout).await?;
+    }
+
+    let mut records: Vec<String> = response
+        .message
+        .answers()
+        .iter()
+        .filter_map(|record| record.data().map(format_rdata))
+        .collect();
+    records.sort();
+
+    Ok(Answer {
+        rcode: response.message.response_code(),
+        records,
+        min_ttl: response.message.answers().iter().map(|record| record.ttl()).min(),
+        scope: response_scope(&response.message),
+        elapsed: response.elapsed,
+    })
+}
+
+/// The scope prefix length from the ECS option echoed in a response.
+fn response_scope(message: &Message) -> Option<u8> {
+    let edns = message.extensions().as_ref()?;
+
+    edns.options().as_ref().values().find_map(|option| match option {
+        EdnsOption::Subnet(_) => Vec::<u8>::try_from(option).ok().and_then(|data| data.get(3).copied()),
+        _ => None,
+    })
+}
+
+fn display_table(subnets: &[Subnet], results: &[Result<Answer>]) {
+    let width = subnets.iter().map(|subnet| subnet.display().len()).max().unwrap_or(6).max(6);
+
+    println!(
+        "{}",
+        format!("  {:<width$}  {:>6}  {:<9} {:>7} {:>10}  Answers", "Subnet", "Scope", "RCODE", "TTL", "Time", width = width)
+            .bright_blue()
+            .bold()
+    );
+
+    for (subnet, result) in subnets.iter().zip(results) {
+        let label = format!("{:<width$}", subnet.display(), width = width);
+
+        match result {
+            Ok(answer) => {
+                let scope = answer.scope.map(|scope| format!("/{}", scope)).unwrap_or_else(|| "-".to_string());
+                let rcode = format!("{:<9}", rcode_name(answer.rcode));
+                let rcode = if answer.rcode == ResponseCode::NoError { rcode.bright_green() } else { rcode.bright_red() };
+                let ttl = answer.min_ttl.map(|ttl| ttl.to_string()).unwrap_or_else(|| "-".to_string());
+                let first = answer.records.first().cloned().unwrap_or_else(|| "(empty)".to_string());
+
+                println!(
+                    "  {}  {:>6}  {} {:>7} {:>10}  {}",
+                    label,
+                    scope.bright_magenta(),
+                    rcode,
+                    ttl.bright_cyan(),
+                    format_duration(answer.elapsed),
+                    first.bright_white()
+                );
+                for record in answer.records.iter().skip(1) {
+                    println!("{:indent$}{}", "", record.bright_white(), indent = width + 42);
+                }
+            }
+            Err(e) => println!("  {}  {}", label, e.to_string().red()),
+        }
+    }
+}
+
+/// Lists which subnets received each distinct answer.
+fn display_groups(subnets: &[Subnet], results: &[Result<Answer>]) {
+    let mut groups: Vec<(&Answer, Vec<String>)> = Vec::new();
+    for (subnet, answer) in subnets.iter().zip(results) {
+        let Ok(answer) = answer else {
+            continue;
+        };
+        let same = |other: &&Answer| other.rcode == answer.rcode && other.records == answer.records;
+        match groups.iter_mut().find(|(existing, _)| same(existing)) {
+            Some((_, members)) => members.push(subnet.display()),
+            None => groups.push((answer, vec![subnet.display()])),
+        }
+    }
+
+    println!();
+    println!("{}", "🗺️ Answer groups".bright_blue().bold());
+    for (answer, members) in &groups {
+        let records = if answer.records.is_empty() {
+            rcode_name(answer.rcode)
+        } else {
+            answer.records.join(", ")
+        };
+        println!("  {} ← {}", records.bright_white(), members.join(", ").bright_cyan());
+    }
+}
+
+fn display_verdict(results: &[Result<Answer>]) {
+    let answers: Vec<&Answer> = results.iter().flatten().collect();
+
+    println!();
+    if answers.is_empty() {
+        println!("{} No subnet got an answer", "❌".red());
+        return;
+    }
+
+    let mut distinct: Vec<&[String]> = answers.iter().map(|answer| answer.records.as_slice()).collect();
+    distinct.sort();
+    distinct.dedup();
+
+    if answers.iter().all(|answer| answer.scope.is_none()) {
+        println!(
+            "{} No response echoed the ECS option: the server ignores it, or a resolver on the path strips it",
+            "⚠️".yellow()
+        );
+        println!("  • Query the CDN's authoritative nameserver directly with -s");
+    } else if answers.iter().all(|answer| answer.scope == Some(0)) {
+        println!("{} Every answer has scope /0: it is the same for all clients", "ℹ️".blue());
+    }
+
+    if distinct.len() > 1 {
+        println!(
+            "{} {} different answer sets across {} subnets: answers depend on the client subnet",
+            "✅".green(),
+            distinct.len(),
+            answers.len()
+        );
+    } else if answers.len() > 1 {
+        println!("{} Every subnet got the same answer", "ℹ️".blue());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trust_dns_resolver::proto::op::Edns;
+
+    #[test]
+    fn test_parse_subnets_file() {
+        let contents = "# CDN regions\n203.0.113.0/24 Frankfurt\n198.51.100.0/24\n\n2001:db8::/48 Tokyo edge\n";
+        let subnets = parse_subnets_file(contents).unwrap();
+
+        assert_eq!(subnets.len(), 3);
+        assert_eq!(subnets[0].display(), "203.0.113.0/24 Frankfurt");
+        assert_eq!(subnets[1].display(), "198.51.100.0/24");
+        assert_eq!(subnets[2].label.as_deref(), Some("Tokyo edge"));
+        assert!(parse_subnets_file("not-a-subnet\n").is_err());
+    }
+
+    #[test]
+    fn test_response_scope() {
+        let mut message = Message::new();
+        assert_eq!(response_scope(&message), None);
+
+        // ECS echoed back with source /24 and scope /20
+        let subnet = parse_subnet("203.0.113.0/24").unwrap();
+        let mut data = client_subnet_option(&subnet);
+        data[3] = 20;
+        let wire = {
+            let mut edns = Edns::new();
+            edns.options_mut().insert(EdnsOption::Unknown(EDNS_CLIENT_SUBNET, data));
+            message.set_edns(edns);
+            message.to_vec().unwrap()
+        };
+
+        let parsed = Message::from_vec(&wire).unwrap();
+        assert_eq!(response_scope(&parsed), Some(20));
+    }
+}
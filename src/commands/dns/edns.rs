@@ -0,0 +1,278 @@
+use anyhow::Result;
+use colored::*;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use trust_dns_resolver::proto::op::ResponseCode;
+use trust_dns_resolver::proto::rr::RecordType;
+
+use super::{parse_record_type, raw_query_server};
+use crate::network::dns::{rcode_name, reverse_name, send_query, DnsQuery, DnsResponse, Transport};
+use crate::utils::format::{format_bytes, format_duration};
+
+/// Payload sizes to try: the classic DNS limit, the DNS Flag Day 2020
+/// recommendation, and the common (fragmentation-prone) default.
+const PAYLOAD_SIZES: [u16; 3] = [512, 1232, 4096];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Delivery {
+    /// Complete answer in one response
+    Full,
+    /// TC=1, the client must retry over TCP
+    Truncated,
+    /// Neither truncated nor complete: fewer records than over TCP
+    Partial,
+    /// No response at all
+    Lost,
+}
+
+struct Probe {
+    transport: Transport,
+    payload: Option<u16>,
+    result: Result<DnsResponse, String>,
+}
+
+pub async fn dns_edns_probe_command(
+    domain: String,
+    server: Option<String>,
+    record_type: String,
+    timeout: Duration,
+) -> Result<()> {
+    println!("{} {}", "📏 EDNS / TRUNCATION PROBE".bright_green().bold(), domain.bright_white().bold());
+
+    let record_type = match parse_record_type(&record_type) {
+        Some(record_type) => record_type,
+        None => {
+            println!("{} Unsupported record type: {}", "❌".red(), record_type);
+            return Ok(());
+        }
+    };
+
+    let (domain, record_type) = match domain.parse::<IpAddr>() {
+        Ok(ip) => (reverse_name(ip), RecordType::PTR),
+        Err(_) => (domain, record_type),
+    };
+
+    let server_addr = match raw_query_server(server.as_deref()) {
+        Ok(addr) => addr,
+        Err(e) => {
+            println!("{} {}", "❌".red(), e);
+            return Ok(());
+        }
+    };
+
+    println!("Query: {} {} (DO=1)", domain.bright_cyan(), format!("{:?}", record_type).bright_yellow());
+    println!("Server: {}", server_addr.to_string().bright_magenta());
+    println!();
+
+    let probes = run_probes(server_addr, &domain, record_type, timeout).await?;
+
+    // TCP has no size limit, so its answer is the reference for "complete"
+    let reference = probes
+        .iter()
+        .find(|probe| probe.transport == Transport::Tcp)
+        .and_then(|probe| probe.result.as_ref().ok())
+        .map(|response| response.message.answers().len());
+
+    display_probes(&probes, reference);
+    display_verdict(&probes, reference);
+
+    Ok(())
+}
+
+async fn run_probes(server: SocketAddr, domain: &str, record_type: RecordType, timeout: Duration) -> Result<Vec<Probe>> {
+    let mut probes = Vec::new();
+
+    // DO=1 adds signatures, which is what pushes real answers past 512 bytes
+    for payload in PAYLOAD_SIZES {
+        let query = DnsQuery::new(domain, record_type)?
+            .edns_payload(Some(payload))
+            .dnssec_ok(true);
+        let result = send_query(server, &query, Transport::Udp, timeout).await;
+        probes.push(Probe {
+            transport: Transport::Udp,
+            payload: Some(payload),
+            result: result.map_err(|e| e.to_string()),
+        });
+    }
+
+    let query = DnsQuery::new(domain, record_type)?.dnssec_ok(true);
+    let result = send_query(server, &query, Transport::Tcp, timeout).await;
+    probes.push(Probe {
+        transport: Transport::Tcp,
+        payload: None,
+        result: result.map_err(|e| e.to_string()),
+    });
+
+    Ok(probes)
+}
+
+fn delivery(probe: &Probe, reference: Option<usize>) -> Delivery {
+    match probe.result {
+        Err(_) => Delivery::Lost,
+        Ok(ref response) if response.message.truncated() => Delivery::Truncated,
+        Ok(ref response) => match reference {
+            Some(expected) if response.message.answers().len() < expected => Delivery::Partial,
+            _ => Delivery::Full,
+        },
+    }
+}
+
+fn display_probes(probes: &[Probe], reference: Option<usize>) {
+    println!("{}", "📊 Results".bright_blue().bold());
+    println!(
+        "{}",
+        format!(
+            "  {:<9} {:>8}  {:<10} {:>9} {:>8} {:>10}  {:<10} {}",
+            "Transport", "Payload", "Result", "Size", "Answers", "Time", "Rcode", "Server max"
+        )
+        .bright_blue()
+    );
+
+    for probe in probes {
+        let payload = probe.payload.map(|p| p.to_string()).unwrap_or_else(|| "-".to_string());
+        let result = match delivery(probe, reference) {
+            Delivery::Full => "FULL".bright_green(),
+            Delivery::Truncated => "TRUNCATED".bright_yellow(),
+            Delivery::Partial => "PARTIAL".bright_yellow(),
+            Delivery::Lost => "NO ANSWER".bright_red(),
+        };
+
+        match probe.result {
+            Ok(ref response) => {
+                let server_max = response
+                    .message
+                    .extensions()
+                    .as_ref()
+                    .map(|edns| edns.max_payload().to_string())
+                    .unwrap_or_else(|| "no EDNS".to_string());
+                println!(
+                    "  {:<9} {:>8}  {:<10} {:>9} {:>8} {:>10}  {:<10} {}",
+                    probe.transport.to_string(),
+                    payload,
+                    result,
+                    format_bytes(response.wire.len() as u64),
+                    response.message.answers().len(),
+                    format_duration(response.elapsed),
+                    rcode_name(response.message.response_code()),
+                    server_max
+                );
+            }
+            Err(ref e) => {
+                println!("  {:<9} {:>8}  {:<10} {}", probe.transport.to_string(), payload, result, e.red());
+            }
+        }
+    }
+}
+
+fn display_verdict(probes: &[Probe], reference: Option<usize>) {
+    println!();
+    println!("{}", "🩺 Verdict".bright_blue().bold());
+
+    let udp: Vec<(u16, Delivery)> = probes
+        .iter()
+        .filter(|probe| probe.transport == Transport::Udp)
+        .map(|probe| (probe.payload.unwrap_or(512), delivery(probe, reference)))
+        .collect();
+    let tcp = probes.iter().find(|probe| probe.transport == Transport::Tcp);
+
+    match tcp.map(|probe| &probe.result) {
+        Some(Ok(response)) if response.message.response_code() == ResponseCode::NoError => println!(
+            "  {} TCP delivers the full answer ({}, {} records)",
+            "✅".green(),
+            format_bytes(response.wire.len() as u64),
+            response.message.answers().len()
+        ),
+        Some(Ok(response)) => println!(
+            "  {} TCP answers with {}",
+            "⚠️".yellow(),
+            rcode_name(response.message.response_code())
+        ),
+        _ => println!(
+            "  {} TCP/53 failed: truncated answers can never be retrieved through this path",
+            "❌".red()
+        ),
+    }
+
+    match udp.iter().find(|(_, delivery)| *delivery == Delivery::Full) {
+        Some((size, _)) => println!("  {} Smallest UDP payload giving the full answer: {} bytes", "✅".green(), size),
+        None => println!("  {} No UDP payload size delivered the full answer", "⚠️".yellow()),
+    }
+
+    let truncated: Vec<String> = udp
+        .iter()
+        .filter(|(_, delivery)| *delivery == Delivery::Truncated)
+        .map(|(size, _)| size.to_string())
+        .collect();
+    if !truncated.is_empty() {
+        println!(
+            "  {} Truncated (TC=1) at {} bytes; clients fall back to TCP",
+            "ℹ️".blue(),
+            truncated.join(", ")
+        );
+    }
+
+    // A larger buffer failing where a smaller one worked points at IP fragments being dropped
+    let lost_after_success = udp.iter().enumerate().any(|(index, (_, delivery))| {
+        *delivery == Delivery::Lost && udp[..index].iter().any(|(_, earlier)| *earlier != Delivery::Lost)
+    });
+    if lost_after_success {
+        println!(
+            "  {} Large UDP responses are lost while smaller ones arrive: fragments are likely dropped",
+            "❌".red()
+        );
+        println!("  • Advertise 1232 bytes (DNS Flag Day 2020) and make sure TCP/53 is open");
+    }
+
+    if udp.iter().all(|(_, delivery)| *delivery == Delivery::Lost) {
+        println!("  {} No UDP response at any size: the server is unreachable or drops EDNS queries", "❌".red());
+    }
+
+    if udp.iter().any(|(_, delivery)| *delivery == Delivery::Partial) {
+        println!(
+            "  {} Some UDP answers had fewer records than TCP without setting TC: the server truncates silently",
+            "⚠️".yellow()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trust_dns_resolver::proto::op::Message;
+    use trust_dns_resolver::proto::rr::{Name, RData, Record};
+
+    fn probe(answers: usize, truncated: bool) -> Probe {
+        let mut message = Message::new();
+        message.set_truncated(truncated);
+        for index in 0..answers {
+            let ip = std::net::Ipv4Addr::new(192, 0, 2, index as u8);
+            message.add_answer(Record::from_rdata(Name::root(), 300, RData::A(ip.into())));
+        }
+
+        Probe {
+            transport: Transport::Udp,
+            payload: Some(512),
+            result: Ok(DnsResponse {
+                message,
+                wire: Vec::new(),
+                elapsed: Duration::ZERO,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_delivery() {
+        assert_eq!(delivery(&probe(3, false), Some(3)), Delivery::Full);
+        assert_eq!(delivery(&probe(0, true), Some(3)), Delivery::Truncated);
+        assert_eq!(delivery(&probe(1, false), Some(3)), Delivery::Partial);
+        assert_eq!(delivery(&probe(1, false), None), Delivery::Full);
+
+        let lost = Probe {
+            transport: Transport::Udp,
+            payload: Some(4096),
+            result: Err("timed out".to_string()),
+        };
+        assert_eq!(delivery(&lost, Some(3)), Delivery::Lost);
+    }
+}
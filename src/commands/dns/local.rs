@@ -0,0 +1,633 @@
+use anyhow::Result;
+use colored::*;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use trust_dns_resolver::proto::op::ResponseCode;
+use trust_dns_resolver::proto::rr::RecordType;
+
+use super::{format_rdata, parse_record_type};
+use crate::network::dns::{rcode_name, send_query, DnsQuery, Transport};
+use crate::utils::format::format_duration;
+
+const RESOLV_CONF: &str = "/etc/resolv.conf";
+const HOSTS: &str = "/etc/hosts";
+const NSSWITCH_CONF: &str = "/etc/nsswitch.conf";
+
+/// glibc only ever uses the first three nameservers.
+const MAXNS: usize = 3;
+
+/// Nameservers slower than this are reported as slow.
+const SLOW_THRESHOLD: Duration = Duration::from_millis(200);
+
+#[derive(Debug, PartialEq)]
+struct ResolvConf {
+    nameservers: Vec<IpAddr>,
+    search: Vec<String>,
+    ndots: u32,
+    timeout: u32,
+    attempts: u32,
+    rotate: bool,
+    /// Options we don't interpret, shown as-is
+    other_options: Vec<String>,
+}
+
+impl Default for ResolvConf {
+    fn default() -> Self {
+        Self {
+            nameservers: Vec::new(),
+            search: Vec::new(),
+            ndots: 1,
+            timeout: 5,
+            attempts: 2,
+            rotate: false,
+            other_options: Vec::new(),
+        }
+    }
+}
+
+impl ResolvConf {
+    /// Parses resolv.conf(5). As in glibc, the last `domain` or `search`
+    /// line wins.
+    fn parse(contents: &str) -> Self {
+        let mut conf = Self::default();
+
+        for line in contents.lines() {
+            let line = line.split(['#', ';']).next().unwrap_or("").trim();
+            let mut fields = line.split_whitespace();
+            let Some(keyword) = fields.next() else {
+                continue;
+            };
+
+            match keyword {
+                "nameserver" => {
+                    // Scoped IPv6 addresses ("fe80::1%eth0") lose their zone here
+                    if let Some(ip) = fields.next().and_then(|ip| ip.split('%').next()?.parse().ok()) {
+                        conf.nameservers.push(ip);
+                    }
+                }
+                "domain" => conf.search = fields.next().map(|d| vec![d.to_string()]).unwrap_or_default(),
+                "search" => conf.search = fields.map(|d| d.to_string()).collect(),
+                "options" => {
+                    for option in fields {
+                        let (name, value) = match option.split_once(':') {
+                            Some((name, value)) => (name, value.parse::<u32>().ok()),
+                            None => (option, None),
+                        };
+                        match (name, value) {
+                            ("ndots", Some(value)) => conf.ndots = value.min(15),
+                            ("timeout", Some(value)) => conf.timeout = value.min(30),
+                            ("attempts", Some(value)) => conf.attempts = value.min(5),
+                            ("rotate", _) => conf.rotate = true,
+                            _ => conf.other_options.push(option.to_string()),
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        conf
+    }
+
+    /// The nameservers glibc queries: the configured ones, or 127.0.0.1
+    /// when there are none.
+    fn effective_nameservers(&self) -> Vec<IpAddr> {
+        if self.nameservers.is_empty() {
+            vec![IpAddr::from([127, 0, 0, 1])]
+        } else {
+            self.nameservers.clone()
+        }
+    }
+
+    /// The names the stub resolver will try for `name`, in order.
+    fn candidates(&self, name: &str) -> Vec<String> {
+        if name.ends_with('.') {
+            return vec![name.to_string()];
+        }
+
+        let absolute = format!("{}.", name);
+        let searched = self
+            .search
+            .iter()
+            .map(|domain| format!("{}.{}.", name, domain.trim_end_matches('.')));
+
+        // Names with at least ndots dots are tried as-is first, others last
+        let dots = name.matches('.').count() as u32;
+        if dots >= self.ndots {
+            std::iter::once(absolute).chain(searched).collect()
+        } else {
+            searched.chain(std::iter::once(absolute)).collect()
+        }
+    }
+}
+
+/// Parses hosts(5) into (address, names) entries.
+fn parse_hosts(contents: &str) -> Vec<(IpAddr, Vec<String>)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('#').next().unwrap_or("").split_whitespace();
+            let ip = fields.next()?.split('%').next()?.parse().ok()?;
+            let names: Vec<String> = fields.map(|name| name.to_string()).collect();
+            (!names.is_empty()).then_some((ip, names))
+        })
+        .collect()
+}
+
+fn hosts_lookup(entries: &[(IpAddr, Vec<String>)], name: &str) -> Vec<IpAddr> {
+    let name = name.trim_end_matches('.');
+    entries
+        .iter()
+        .filter(|(_, names)| names.iter().any(|n| n.eq_ignore_ascii_case(name)))
+        .map(|(ip, _)| *ip)
+        .collect()
+}
+
+/// The result a name service reports for a lookup (nsswitch.conf(5)).
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NssStatus {
+    Success,
+    NotFound,
+    Unavail,
+    TryAgain,
+}
+
+impl NssStatus {
+    const ALL: [NssStatus; 4] = [Self::Success, Self::NotFound, Self::Unavail, Self::TryAgain];
+
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "SUCCESS" => Some(Self::Success),
+            "NOTFOUND" => Some(Self::NotFound),
+            "UNAVAIL" => Some(Self::Unavail),
+            "TRYAGAIN" => Some(Self::TryAgain),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Success => "SUCCESS",
+            Self::NotFound => "NOTFOUND",
+            Self::Unavail => "UNAVAIL",
+            Self::TryAgain => "TRYAGAIN",
+        }
+    }
+}
+
+/// One source on the `hosts:` line and the statuses that end the lookup
+/// there; by default only SUCCESS does.
+#[derive(Debug, PartialEq)]
+struct NssSource {
+    name: String,
+    returns_on: Vec<NssStatus>,
+}
+
+impl NssSource {
+    fn returns_on(&self, status: NssStatus) -> bool {
+        self.returns_on.contains(&status)
+    }
+}
+
+/// The raw `hosts:` line of nsswitch.conf, without the keyword.
+fn nsswitch_hosts_line(contents: &str) -> Option<String> {
+    contents.lines().find_map(|line| {
+        let line = line.split('#').next().unwrap_or("").trim();
+        Some(line.strip_prefix("hosts:")?.trim().to_string())
+    })
+}
+
+/// Parses the sources and their `[STATUS=action ...]` criteria. `!STATUS`
+/// applies the action to every other status; `merge` is treated as
+/// `continue`, as it only matters for group lookups.
+fn parse_nss_sources(line: &str) -> Vec<NssSource> {
+    let mut sources: Vec<NssSource> = Vec::new();
+    let mut in_brackets = false;
+
+    for token in line.split_whitespace() {
+        let mut item = token;
+        if let Some(rest) = item.strip_prefix('[') {
+            in_brackets = true;
+            item = rest;
+        }
+        let closes = item.ends_with(']');
+        item = item.trim_end_matches(']');
+
+        if !in_brackets {
+            sources.push(NssSource {
+                name: item.to_string(),
+                returns_on: vec![NssStatus::Success],
+            });
+        } else if let (Some(source), Some((status, action))) = (sources.last_mut(), item.split_once('=')) {
+            let (negated, status) = match status.strip_prefix('!') {
+                Some(status) => (true, status),
+                None => (false, status),
+            };
+            if let Some(status) = NssStatus::parse(status) {
+                let returns = action.eq_ignore_ascii_case("return");
+                for affected in NssStatus::ALL.into_iter().filter(|other| (*other == status) != negated) {
+                    source.returns_on.retain(|existing| *existing != affected);
+                    if returns {
+                        source.returns_on.push(affected);
+                    }
+                }
+            }
+        }
+
+        if closes {
+            in_brackets = false;
+        }
+    }
+
+    sources
+}
+
+pub async fn dns_diagnose_local_command(domain: Option<String>, record_type: String, timeout: Duration) -> Result<()> {
+    println!("{}", "🩺 LOCAL RESOLVER DIAGNOSTICS".bright_green().bold());
+
+    let record_type = match parse_record_type(&record_type) {
+        Some(record_type) => record_type,
+        None => {
+            println!("{} Unsupported record type: {}", "❌".red(), record_type);
+            return Ok(());
+        }
+    };
+
+    println!();
+    println!("{}", format!("📄 {}", RESOLV_CONF).bright_blue().bold());
+    let conf = match std::fs::read_to_string(RESOLV_CONF) {
+        Ok(contents) => ResolvConf::parse(&contents),
+        Err(e) => {
+            println!("  {} Cannot read {}: {}", "❌".red(), RESOLV_CONF, e);
+            ResolvConf::default()
+        }
+    };
+    display_resolv_conf(&conf);
+
+    println!();
+    println!("{}", format!("📄 {}", HOSTS).bright_blue().bold());
+    let hosts = match std::fs::read_to_string(HOSTS) {
+        Ok(contents) => {
+            let hosts = parse_hosts(&contents);
+            println!("  {} entries", hosts.len().to_string().bright_white());
+            hosts
+        }
+        Err(e) => {
+            println!("  {} Cannot read {}: {}", "⚠️".yellow(), HOSTS, e);
+            Vec::new()
+        }
+    };
+
+    println!();
+    println!("{}", format!("📄 {}", NSSWITCH_CONF).bright_blue().bold());
+    let sources = match std::fs::read_to_string(NSSWITCH_CONF).ok().and_then(|c| nsswitch_hosts_line(&c)) {
+        Some(line) => {
+            println!("  hosts: {}", line.bright_white());
+            parse_nss_sources(&line)
+        }
+        None => {
+            println!("  {} No hosts line, assuming \"files dns\"", "ℹ️".blue());
+            parse_nss_sources("files dns")
+        }
+    };
+    if sources.iter().any(|source| source.name == "resolve") {
+        println!(
+            "  {} Names go through systemd-resolved (\"resolve\"); check resolvectl status too",
+            "ℹ️".blue()
+        );
+    }
+
+    println!();
+    println!("{}", "📡 Nameserver health".bright_blue().bold());
+    // Probe with the name being diagnosed, or the root zone when there is none
+    let (probe_name, probe_type) = match domain {
+        Some(ref domain) => (format!("{}.", domain.trim_end_matches('.')), record_type),
+        None => (".".to_string(), RecordType::NS),
+    };
+    check_nameservers(&conf, &probe_name, probe_type, timeout).await;
+
+    if let Some(domain) = domain {
+        println!();
+        println!(
+            "{} {} {}",
+            "🔎 Resolution path for".bright_blue().bold(),
+            domain.bright_white().bold(),
+            format!("{:?}", record_type).bright_yellow()
+        );
+        trace_resolution(&conf, &hosts, &sources, &domain, record_type, timeout).await;
+    }
+
+    Ok(())
+}
+
+fn display_resolv_conf(conf: &ResolvConf) {
+    if conf.nameservers.is_empty() {
+        println!("  {} No nameservers configured; glibc falls back to 127.0.0.1", "⚠️".yellow());
+    }
+    for (index, ip) in conf.nameservers.iter().enumerate() {
+        let note = if index >= MAXNS {
+            " (ignored: only the first 3 are used)".bright_red().to_string()
+        } else if ip.is_loopback() {
+            " (local stub resolver)".bright_black().to_string()
+        } else {
+            String::new()
+        };
+        println!("  nameserver {}{}", ip.to_string().bright_magenta(), note);
+    }
+
+    let search = if conf.search.is_empty() { "(none)".to_string() } else { conf.search.join(" ") };
+    println!("  search     {}", search.bright_cyan());
+    println!(
+        "  ndots {}  timeout {}s  attempts {}  rotate {}",
+        conf.ndots.to_string().bright_white(),
+        conf.timeout.to_string().bright_white(),
+        conf.attempts.to_string().bright_white(),
+        if conf.rotate { "yes".bright_white() } else { "no".bright_white() }
+    );
+    if !conf.other_options.is_empty() {
+        println!("  options    {}", conf.other_options.join(" "));
+    }
+
+    if conf.ndots > 1 && !conf.search.is_empty() {
+        println!(
+            "  {} ndots:{} sends names with fewer dots through every search domain first",
+            "⚠️".yellow(),
+            conf.ndots
+        );
+    }
+    if conf.search.len() > 6 {
+        println!("  {} Long search list: each failed lookup costs {} extra queries", "⚠️".yellow(), conf.search.len());
+    }
+    let worst_case = conf.timeout * conf.attempts * conf.nameservers.len().clamp(1, MAXNS) as u32;
+    if worst_case > 10 {
+        println!("  {} A dead resolver can stall lookups for up to {}s", "⚠️".yellow(), worst_case);
+    }
+}
+
+async fn check_nameservers(conf: &ResolvConf, name: &str, record_type: RecordType, timeout: Duration) {
+    let nameservers = conf.effective_nameservers();
+    let mut handles = Vec::new();
+    for ip in &nameservers {
+        let server = SocketAddr::new(*ip, 53);
+        let name = name.to_string();
+        handles.push(tokio::spawn(async move {
+            let query = DnsQuery::new(&name, record_type)?;
+            send_query(server, &query, Transport::Udp, timeout).await
+        }));
+    }
+
+    for (ip, handle) in nameservers.iter().zip(handles) {
+        let result = match handle.await {
+            Ok(result) => result,
+            Err(e) => Err(anyhow::anyhow!("{}", e)),
+        };
+
+        let status = match result {
+            Ok(response) => {
+                let rcode = response.message.response_code();
+                let latency = format_duration(response.elapsed);
+                match rcode {
+                    ResponseCode::NoError | ResponseCode::NXDomain if response.elapsed > SLOW_THRESHOLD => {
+                        format!("{} {} ({})", "SLOW".bright_yellow().bold(), latency.bright_yellow(), rcode_name(rcode))
+                    }
+                    ResponseCode::NoError | ResponseCode::NXDomain => {
+                        format!("{} {} ({})", "OK".bright_green().bold(), latency.bright_green(), rcode_name(rcode))
+                    }
+                    ResponseCode::Refused => format!("{} {} (REFUSED: not recursive for us)", "BROKEN".bright_red().bold(), latency),
+                    _ => format!("{} {} ({})", "BROKEN".bright_red().bold(), latency, rcode_name(rcode)),
+                }
+            }
+            Err(e) => format!("{} {}", "DEAD".bright_red().bold(), e.to_string().red()),
+        };
+
+        println!("  {:<39} {}", ip.to_string().bright_magenta(), status);
+    }
+}
+
+/// Walks the nsswitch sources the way glibc would, showing which one answers
+/// and every DNS query the search list produces. After each source its
+/// status decides, through the `[STATUS=action]` criteria, whether the
+/// lookup ends there.
+async fn trace_resolution(
+    conf: &ResolvConf,
+    hosts: &[(IpAddr, Vec<String>)],
+    sources: &[NssSource],
+    name: &str,
+    record_type: RecordType,
+    timeout: Duration,
+) {
+    for source in sources {
+        let status = match source.name.as_str() {
+            "files" => {
+                let addresses: Vec<IpAddr> = if matches!(record_type, RecordType::A | RecordType::AAAA) {
+                    hosts_lookup(hosts, name)
+                        .into_iter()
+                        .filter(|ip| ip.is_ipv4() == (record_type == RecordType::A))
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+
+                if addresses.is_empty() {
+                    println!("  {} files: not in {}", "·".bright_black(), HOSTS);
+                    NssStatus::NotFound
+                } else {
+                    let list: Vec<String> = addresses.iter().map(|ip| ip.to_string()).collect();
+                    println!("  {} files: {} → {}", "✅".green(), HOSTS, list.join(", ").bright_white());
+                    NssStatus::Success
+                }
+            }
+            "dns" => trace_dns(conf, name, record_type, timeout).await,
+            minimal if minimal.starts_with("mdns") && minimal.ends_with("_minimal") && !is_local_name(name) => {
+                println!("  {} {}: only handles .local names", "·".bright_black(), minimal);
+                NssStatus::Unavail
+            }
+            other => {
+                // Without querying it we can only say what its answer would do
+                let stops_on: Vec<&str> = NssStatus::ALL
+                    .into_iter()
+                    .filter(|status| source.returns_on(*status))
+                    .map(NssStatus::name)
+                    .collect();
+                println!(
+                    "  {} {}: not checked by netdiag; the lookup ends here on {}",
+                    "·".bright_black(),
+                    other,
+                    stops_on.join(", ")
+                );
+                if source.returns_on(NssStatus::NotFound) {
+                    println!(
+                        "  {} Unless {} is unavailable, later sources are never asked ([NOTFOUND=return])",
+                        "⚠️".yellow(),
+                        other
+                    );
+                    return;
+                }
+                continue;
+            }
+        };
+
+        if !source.returns_on(status) {
+            continue;
+        }
+        match (status, source.name.as_str()) {
+            (NssStatus::Success, "files") => println!("  {} Answered by {}; DNS is never asked", "ℹ️".blue(), HOSTS),
+            (NssStatus::Success, _) => {}
+            (status, source_name) => {
+                println!(
+                    "  {} {} reported {} and [{}=return] ends the lookup; applications see a failure",
+                    "❌".red(),
+                    source_name,
+                    status.name(),
+                    status.name()
+                );
+            }
+        }
+        return;
+    }
+
+    println!("  {} No source answered; applications will see a lookup failure", "❌".red());
+}
+
+/// Names nss-mdns resolves in minimal mode.
+fn is_local_name(name: &str) -> bool {
+    let name = name.trim_end_matches('.').to_ascii_lowercase();
+    name == "local" || name.ends_with(".local")
+}
+
+/// Queries every search-list candidate in turn; NOTFOUND once a server
+/// said the name or data does not exist, TRYAGAIN when none could answer.
+async fn trace_dns(conf: &ResolvConf, name: &str, record_type: RecordType, timeout: Duration) -> NssStatus {
+    let server = SocketAddr::new(conf.effective_nameservers()[0], 53);
+
+    let candidates = conf.candidates(name);
+    println!(
+        "  dns: {} candidate name(s) via {} (ndots:{}, {} dots in name)",
+        candidates.len(),
+        server.to_string().bright_magenta(),
+        conf.ndots,
+        name.trim_end_matches('.').matches('.').count()
+    );
+
+    let mut status = NssStatus::TryAgain;
+    for candidate in candidates {
+        let result = match DnsQuery::new(&candidate, record_type) {
+            Ok(query) => send_query(server, &query, Transport::Udp, timeout).await,
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(response) => {
+                let message = &response.message;
+                let answers: Vec<String> = message
+                    .answers()
+                    .iter()
+                    .filter(|record| record.record_type() == record_type)
+                    .filter_map(|record| record.data().map(format_rdata))
+                    .collect();
+                let rcode = rcode_name(message.response_code());
+                let latency = format_duration(response.elapsed);
+
+                if message.response_code() == ResponseCode::NoError && !answers.is_empty() {
+                    println!(
+                        "    {} {} → {} ({})",
+                        "✅".green(),
+                        candidate.bright_cyan(),
+                        answers.join(", ").bright_white(),
+                        latency
+                    );
+                    println!("  {} Answered by DNS as {}", "ℹ️".blue(), candidate.bright_cyan());
+                    return NssStatus::Success;
+                }
+                if matches!(message.response_code(), ResponseCode::NoError | ResponseCode::NXDomain) {
+                    status = NssStatus::NotFound;
+                }
+
+                let outcome = if message.response_code() == ResponseCode::NoError { "NODATA".to_string() } else { rcode };
+                println!("    {} {} → {} ({})", "✗".bright_red(), candidate, outcome.bright_yellow(), latency);
+            }
+            Err(e) => println!("    {} {} → {}", "✗".bright_red(), candidate, e.to_string().red()),
+        }
+    }
+
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_resolv_conf() {
+        let conf = ResolvConf::parse(
+            "# generated\nnameserver 10.0.0.2\nnameserver fe80::1%eth0\ndomain old.example\n\
+             search corp.example example.com ; trailing\noptions ndots:5 timeout:2 rotate edns0\n",
+        );
+
+        assert_eq!(conf.nameservers, vec!["10.0.0.2".parse::<IpAddr>().unwrap(), "fe80::1".parse().unwrap()]);
+        assert_eq!(conf.search, vec!["corp.example", "example.com"]);
+        assert_eq!(conf.ndots, 5);
+        assert_eq!(conf.timeout, 2);
+        assert_eq!(conf.attempts, 2);
+        assert!(conf.rotate);
+        assert_eq!(conf.other_options, vec!["edns0"]);
+    }
+
+    #[test]
+    fn test_candidates_follow_ndots() {
+        let conf = ResolvConf {
+            search: vec!["corp.example".to_string(), "example.com".to_string()],
+            ..ResolvConf::default()
+        };
+
+        assert_eq!(conf.candidates("db"), vec!["db.corp.example.", "db.example.com.", "db."]);
+        assert_eq!(
+            conf.candidates("www.example.org"),
+            vec!["www.example.org.", "www.example.org.corp.example.", "www.example.org.example.com."]
+        );
+        assert_eq!(conf.candidates("absolute.example."), vec!["absolute.example."]);
+
+        let kubernetes = ResolvConf { ndots: 5, ..conf };
+        assert_eq!(kubernetes.candidates("www.example.org")[0], "www.example.org.corp.example.");
+    }
+
+    #[test]
+    fn test_parse_hosts() {
+        let hosts = parse_hosts("127.0.0.1 localhost\n::1 localhost ip6-localhost # loopback\n\n10.0.0.5 db.corp db\n");
+
+        assert_eq!(hosts.len(), 3);
+        assert_eq!(hosts_lookup(&hosts, "DB"), vec!["10.0.0.5".parse::<IpAddr>().unwrap()]);
+        assert_eq!(hosts_lookup(&hosts, "localhost.").len(), 2);
+        assert!(hosts_lookup(&hosts, "missing").is_empty());
+    }
+
+    #[test]
+    fn test_parse_nsswitch_hosts() {
+        let line = nsswitch_hosts_line("passwd: files\nhosts:  files mdns4_minimal [NOTFOUND=return] dns\n").unwrap();
+        assert_eq!(line, "files mdns4_minimal [NOTFOUND=return] dns");
+        assert!(nsswitch_hosts_line("passwd: files\n").is_none());
+
+        let sources = parse_nss_sources(&line);
+        let names: Vec<&str> = sources.iter().map(|source| source.name.as_str()).collect();
+        assert_eq!(names, ["files", "mdns4_minimal", "dns"]);
+        assert_eq!(sources[0].returns_on, [NssStatus::Success]);
+        assert!(sources[1].returns_on(NssStatus::NotFound));
+        assert!(!sources[1].returns_on(NssStatus::Unavail));
+
+        // systemd's recommended line: only fall through when resolved is not running
+        let sources = parse_nss_sources("resolve [!UNAVAIL=return] files [SUCCESS=continue NOTFOUND=return] dns");
+        assert!(sources[0].returns_on(NssStatus::NotFound) && sources[0].returns_on(NssStatus::TryAgain));
+        assert!(!sources[0].returns_on(NssStatus::Unavail));
+        assert_eq!(sources[1].returns_on, [NssStatus::NotFound]);
+        assert_eq!(sources[2].name, "dns");
+    }
+
+    #[test]
+    fn test_is_local_name() {
+        assert!(is_local_name("printer.local."));
+        assert!(is_local_name("Printer.LOCAL"));
+        assert!(!is_local_name("example.com"));
+        assert!(!is_local_name("notlocal"));
+    }
+}
@@ -0,0 +1,428 @@
+use anyhow::Result;
+use colored::*;
+use std::net::IpAddr;
+use std::time::Duration;
+
+use trust_dns_resolver::proto::op::{Message, ResponseCode};
+use trust_dns_resolver::proto::rr::rdata::opt::EdnsOption;
+use trust_dns_resolver::proto::rr::{Record, RecordType};
+
+use super::{format_rdata, parse_record_type, raw_query_server};
+use crate::network::dns::{
+    op_code_name, rcode_name, reverse_name, send_query, wire_op_code, DnsQuery, DnsResponse, Transport,
+};
+use crate::utils::format::{format_duration, format_hex_dump};
+
+pub(super) const EDNS_NSID: u16 = 3;
+pub(super) const EDNS_CLIENT_SUBNET: u16 = 8;
+const EDNS_COOKIE: u16 = 10;
+const EDNS_PADDING: u16 = 12;
+const EDNS_EXTENDED_ERROR: u16 = 15;
+
+pub async fn dns_verbose_command(
+    domain: String,
+    server: Option<String>,
+    record_type: String,
+    show_hex: bool,
+    timeout: Duration,
+) -> Result<()> {
+    println!("{} {}", "🌐 DNS LOOKUP".bright_green().bold(), domain.bright_white().bold());
+
+    let record_type = match parse_record_type(&record_type) {
+        Some(record_type) => record_type,
+        None => {
+            println!("{} Unsupported record type: {}", "❌".red(), record_type);
+            return Ok(());
+        }
+    };
+
+    // An address means a PTR query for its reverse name
+    let (domain, record_type) = match domain.parse::<IpAddr>() {
+        Ok(ip) => (reverse_name(ip), RecordType::PTR),
+        Err(_) => (domain, record_type),
+    };
+
+    let server_addr = match raw_query_server(server.as_deref()) {
+        Ok(addr) => addr,
+        Err(e) => {
+            println!("{} {}", "❌".red(), e);
+            return Ok(());
+        }
+    };
+
+    // Ask for the server identity and send a client cookie, as dig does
+    let client_cookie: [u8; 8] = rand::random();
+    let query = DnsQuery::new(&domain, record_type)?
+        .edns_option(EDNS_NSID, Vec::new())
+        .edns_option(EDNS_COOKIE, client_cookie.to_vec());
+
+    println!("Query: {} {}", domain.bright_cyan(), format!("{:?}", record_type).bright_yellow());
+    println!("Server: {}", server_addr.to_string().bright_magenta());
+    println!();
+
+    let mut response = match send_query(server_addr, &query, Transport::Udp, timeout).await {
+        Ok(response) => response,
+        Err(e) => {
+            println!("{} DNS query failed: {}", "❌".red(), e.to_string().red());
+            return Ok(());
+        }
+    };
+    let mut transport = Transport::Udp;
+
+    if response.message.truncated() {
+        println!(
+            "{} Response truncated (TC=1, {} bytes over UDP), retrying over TCP",
+            "⚠️".yellow(),
+            response.wire.len()
+        );
+        println!();
+        match send_query(server_addr, &query, Transport::Tcp, timeout).await {
+            Ok(tcp_response) => {
+                response = tcp_response;
+                transport = Transport::Tcp;
+            }
+            Err(e) => {
+                println!("{} TCP retry failed: {}, showing truncated response", "❌".red(), e.to_string().red());
+                println!();
+            }
+        }
+    }
+
+    display_response(&response, record_type, transport);
+
+    if show_hex {
+        println!();
+        println!("{} ({} bytes)", "🔢 Wire format".bright_blue().bold(), response.wire.len());
+        for line in format_hex_dump(&response.wire).lines() {
+            println!("  {}", line.bright_white());
+        }
+    }
+
+    Ok(())
+}
+
+pub(super) fn display_response(response: &DnsResponse, record_type: RecordType, transport: Transport) {
+    let message = &response.message;
+
+    display_header(message, wire_op_code(&response.wire));
+    println!();
+    display_outcome(message, record_type);
+
+    if let Some(edns) = message.extensions() {
+        println!();
+        println!("{}", "🧩 EDNS".bright_blue().bold());
+        println!(
+            "  Version: {}  UDP payload: {}  DO: {}",
+            edns.version().to_string().bright_white(),
+            edns.max_payload().to_string().bright_white(),
+            flag(edns.dnssec_ok())
+        );
+
+        let mut options: Vec<(u16, Vec<u8>)> = edns
+            .options()
+            .as_ref()
+            .values()
+            .map(|option| match option {
+                EdnsOption::Unknown(code, data) => (*code, data.clone()),
+                other => (EDNS_CLIENT_SUBNET, Vec::<u8>::try_from(other).unwrap_or_default()),
+            })
+            .collect();
+        options.sort_by_key(|(code, _)| *code);
+
+        for (code, data) in options {
+            println!("  {}", describe_edns_option(code, &data));
+        }
+    }
+
+    for (title, records) in [
+        ("Question", None),
+        ("Answer", Some(message.answers())),
+        ("Authority", Some(message.name_servers())),
+        ("Additional", Some(message.additionals())),
+    ] {
+        println!();
+        match records {
+            None => {
+                println!("{}", format!("❓ {} section", title).bright_blue().bold());
+                for query in message.queries() {
+                    println!(
+                        "  {} {} {}",
+                        query.name().to_string().bright_white(),
+                        query.query_class().to_string().bright_cyan(),
+                        query.query_type().to_string().bright_yellow()
+                    );
+                }
+            }
+            Some(records) => {
+                println!("{}", format!("📄 {} section ({})", title, records.len()).bright_blue().bold());
+                for record in records {
+                    println!("  {}", format_record(record));
+                }
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "{} Query time: {} via {} ({} bytes)",
+        "⏱️".bright_blue(),
+        format_duration(response.elapsed).bright_white(),
+        transport.to_string().bright_cyan(),
+        response.wire.len()
+    );
+}
+
+/// `op_code` comes from the wire, as trust-dns cannot represent unassigned opcodes.
+fn display_header(message: &Message, op_code: u8) {
+    let rcode = rcode_name(message.response_code());
+    let rcode = if message.response_code() == ResponseCode::NoError {
+        rcode.bright_green()
+    } else {
+        rcode.bright_red()
+    };
+
+    println!("{}", "📋 Header".bright_blue().bold());
+    println!(
+        "  ID: {}  Opcode: {}  Status: {}",
+        message.id().to_string().bright_white(),
+        op_code_name(op_code).bright_white(),
+        rcode.bold()
+    );
+    println!(
+        "  Flags: AA {}  TC {}  RD {}  RA {}  AD {}  CD {}",
+        flag(message.authoritative()),
+        flag(message.truncated()),
+        flag(message.recursion_desired()),
+        flag(message.recursion_available()),
+        flag(message.authentic_data()),
+        flag(message.checking_disabled())
+    );
+    println!(
+        "  Counts: QUERY {}  ANSWER {}  AUTHORITY {}  ADDITIONAL {}",
+        message.queries().len(),
+        message.answers().len(),
+        message.name_servers().len(),
+        message.additionals().len() + usize::from(message.extensions().is_some())
+    );
+}
+
+/// What the rcode and the sections mean together.
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Answered,
+    /// CNAMEs or other records, but none of the queried type
+    AliasesOnly,
+    Referral,
+    /// NOERROR with nothing of the queried type: the name exists
+    NoData,
+    NxDomain,
+    ServFail,
+    Refused,
+    Other(ResponseCode),
+}
+
+fn classify_outcome(message: &Message, record_type: RecordType) -> Outcome {
+    let answered = message.answers().iter().any(|record| record.record_type() == record_type);
+
+    match message.response_code() {
+        ResponseCode::NoError if answered => Outcome::Answered,
+        ResponseCode::NoError if !message.answers().is_empty() => Outcome::AliasesOnly,
+        ResponseCode::NoError
+            if message.name_servers().iter().any(|r| r.record_type() == RecordType::NS) && !message.authoritative() =>
+        {
+            Outcome::Referral
+        }
+        ResponseCode::NoError => Outcome::NoData,
+        ResponseCode::NXDomain => Outcome::NxDomain,
+        ResponseCode::ServFail => Outcome::ServFail,
+        ResponseCode::Refused => Outcome::Refused,
+        other => Outcome::Other(other),
+    }
+}
+
+/// Explains what the rcode and sections mean together, e.g. NODATA vs NXDOMAIN.
+fn display_outcome(message: &Message, record_type: RecordType) {
+    let (icon, text) = match classify_outcome(message, record_type) {
+        Outcome::Answered => ("✅".green(), format!("{} records found", record_type).bright_green()),
+        Outcome::AliasesOnly => (
+            "↪️".yellow(),
+            format!("Only aliases returned, no {} records at the target", record_type).bright_yellow(),
+        ),
+        Outcome::Referral => ("↪️".yellow(), "Referral to another zone's nameservers".bright_yellow()),
+        Outcome::NoData => (
+            "ℹ️".blue(),
+            format!("NODATA: the name exists but has no {} records", record_type).bright_yellow(),
+        ),
+        Outcome::NxDomain => ("❌".red(), "NXDOMAIN: the name does not exist".bright_red()),
+        Outcome::ServFail => (
+            "💥".red(),
+            "SERVFAIL: the server could not complete the lookup (upstream failure or DNSSEC validation)".bright_red(),
+        ),
+        Outcome::Refused => ("⛔".red(), "REFUSED: the server declined to answer this query".bright_red()),
+        Outcome::Other(rcode) => ("❓".yellow(), format!("{} response", rcode_name(rcode)).bright_yellow()),
+    };
+
+    println!("{} {}", icon, text);
+    if message.truncated() {
+        println!("{} Response is truncated (TC=1); sections may be incomplete", "⚠️".yellow());
+    }
+}
+
+fn format_record(record: &Record) -> String {
+    let data = record.data().map(format_rdata).unwrap_or_default();
+    format!(
+        "{} {} {} {} {}",
+        record.name().to_string().bright_white(),
+        record.ttl().to_string().bright_cyan(),
+        record.dns_class().to_string().bright_cyan(),
+        record.record_type().to_string().bright_yellow(),
+        data.bright_white()
+    )
+}
+
+fn describe_edns_option(code: u16, data: &[u8]) -> String {
+    match code {
+        EDNS_NSID => {
+            let text = String::from_utf8_lossy(data);
+            format!("{}: {} (\"{}\")", "NSID".bright_yellow(), hex(data), text.bright_white())
+        }
+        EDNS_CLIENT_SUBNET if data.len() >= 4 => {
+            let family = u16::from_be_bytes([data[0], data[1]]);
+            format!(
+                "{}: family {} source /{} scope /{} address {}",
+                "CLIENT-SUBNET".bright_yellow(),
+                family,
+                data[2],
+                data[3],
+                hex(&data[4..])
+            )
+        }
+        EDNS_COOKIE if data.len() >= 8 => {
+            let server = if data.len() > 8 { hex(&data[8..]) } else { "(none)".to_string() };
+            format!("{}: client {} server {}", "COOKIE".bright_yellow(), hex(&data[..8]), server)
+        }
+        EDNS_PADDING => format!("{}: {} bytes", "PADDING".bright_yellow(), data.len()),
+        EDNS_EXTENDED_ERROR if data.len() >= 2 => {
+            let info_code = u16::from_be_bytes([data[0], data[1]]);
+            let text = String::from_utf8_lossy(&data[2..]);
+            format!("{}: {} ({}) {}", "EDE".bright_yellow(), info_code, extended_error_name(info_code), text)
+        }
+        _ => format!("{}: {}", format!("OPTION {}", code).bright_yellow(), hex(data)),
+    }
+}
+
+/// RFC 8914 extended DNS error codes.
+fn extended_error_name(info_code: u16) -> &'static str {
+    match info_code {
+        0 => "Other",
+        1 => "Unsupported DNSKEY Algorithm",
+        2 => "Unsupported DS Digest Type",
+        3 => "Stale Answer",
+        4 => "Forged Answer",
+        5 => "DNSSEC Indeterminate",
+        6 => "DNSSEC Bogus",
+        7 => "Signature Expired",
+        8 => "Signature Not Yet Valid",
+        9 => "DNSKEY Missing",
+        10 => "RRSIGs Missing",
+        11 => "No Zone Key Bit Set",
+        12 => "NSEC Missing",
+        13 => "Cached Error",
+        14 => "Not Ready",
+        15 => "Blocked",
+        16 => "Censored",
+        17 => "Filtered",
+        18 => "Prohibited",
+        19 => "Stale NXDOMAIN Answer",
+        20 => "Not Authoritative",
+        21 => "Not Supported",
+        22 => "No Reachable Authority",
+        23 => "Network Error",
+        24 => "Invalid Data",
+        _ => "Unknown",
+    }
+}
+
+fn flag(set: bool) -> ColoredString {
+    if set {
+        "✓".bright_green()
+    } else {
+        "·".bright_black()
+    }
+}
+
+fn hex(data: &[u8]) -> String {
+    if data.is_empty() {
+        return "(empty)".to_string();
+    }
+    data.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trust_dns_resolver::proto::rr::rdata::{CNAME, NS, SOA};
+    use trust_dns_resolver::proto::rr::{Name, RData};
+
+    fn name(text: &str) -> Name {
+        Name::from_ascii(text).unwrap()
+    }
+
+    fn response(rcode: ResponseCode) -> Message {
+        let mut message = Message::new();
+        message.set_response_code(rcode);
+        message
+    }
+
+    fn soa() -> Record {
+        let soa = SOA::new(name("ns1.example.test."), name("hostmaster.example.test."), 1, 3600, 600, 86400, 300);
+        Record::from_rdata(name("example.test."), 300, RData::SOA(soa))
+    }
+
+    #[test]
+    fn test_classify_answers() {
+        let mut answered = response(ResponseCode::NoError);
+        answered.add_answer(Record::from_rdata(name("www.example.test."), 300, RData::A("192.0.2.1".parse().unwrap())));
+        assert_eq!(classify_outcome(&answered, RecordType::A), Outcome::Answered);
+        assert_eq!(classify_outcome(&answered, RecordType::AAAA), Outcome::AliasesOnly);
+
+        let mut alias = response(ResponseCode::NoError);
+        alias.add_answer(Record::from_rdata(name("www.example.test."), 300, RData::CNAME(CNAME(name("cdn.example.net.")))));
+        assert_eq!(classify_outcome(&alias, RecordType::A), Outcome::AliasesOnly);
+    }
+
+    #[test]
+    fn test_classify_negative_answers() {
+        // Both carry the zone's SOA; only the rcode tells them apart
+        let mut nodata = response(ResponseCode::NoError);
+        nodata.set_authoritative(true);
+        nodata.add_name_server(soa());
+        assert_eq!(classify_outcome(&nodata, RecordType::AAAA), Outcome::NoData);
+
+        let mut nxdomain = response(ResponseCode::NXDomain);
+        nxdomain.set_authoritative(true);
+        nxdomain.add_name_server(soa());
+        assert_eq!(classify_outcome(&nxdomain, RecordType::A), Outcome::NxDomain);
+
+        // An empty NOERROR from a recursive resolver is NODATA too
+        assert_eq!(classify_outcome(&response(ResponseCode::NoError), RecordType::MX), Outcome::NoData);
+    }
+
+    #[test]
+    fn test_classify_referral_and_failures() {
+        let mut referral = response(ResponseCode::NoError);
+        referral.add_name_server(Record::from_rdata(name("example.test."), 172800, RData::NS(NS(name("ns1.example.test.")))));
+        assert_eq!(classify_outcome(&referral, RecordType::A), Outcome::Referral);
+
+        // The same NS records from the zone's own server are not a referral
+        referral.set_authoritative(true);
+        assert_eq!(classify_outcome(&referral, RecordType::A), Outcome::NoData);
+
+        assert_eq!(classify_outcome(&response(ResponseCode::ServFail), RecordType::A), Outcome::ServFail);
+        assert_eq!(classify_outcome(&response(ResponseCode::Refused), RecordType::A), Outcome::Refused);
+        assert_eq!(
+            classify_outcome(&response(ResponseCode::NotImp), RecordType::A),
+            Outcome::Other(ResponseCode::NotImp)
+        );
+    }
+}
@@ -0,0 +1,253 @@
+use anyhow::Result;
+use colored::*;
+use trust_dns_resolver::config::*;
+use trust_dns_resolver::TokioAsyncResolver;
+use trust_dns_resolver::proto::rr::{RecordType, RData};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use crate::network::dns::{parse_server, system_nameservers};
+use crate::utils::format::format_duration;
+
+mod axfr;
+mod batch;
+mod bench;
+mod compare;
+mod dnsbl;
+mod ecs;
+mod edns;
+mod local;
+mod message;
+mod query;
+mod reverse;
+mod targets;
+mod watch;
+
+pub use axfr::dns_axfr_command;
+pub use batch::dns_batch_command;
+pub use bench::dns_bench_command;
+pub use compare::dns_compare_command;
+pub use dnsbl::dns_dnsbl_command;
+pub use ecs::dns_ecs_command;
+pub use edns::dns_edns_probe_command;
+pub use local::dns_diagnose_local_command;
+pub use message::dns_verbose_command;
+pub use query::{dns_query_command, QueryOptions};
+pub use reverse::{dns_reverse_command, is_reverse_target};
+pub use watch::dns_watch_command;
+
+pub async fn dns_command(domain: String, server: Option<String>, record_type: String, timeout: Duration) -> Result<()> {
+    println!("{} {}", "🌐 DNS LOOKUP".bright_green().bold(), domain.bright_white().bold());
+    
+    // Parse record type
+    let record_type = match parse_record_type(&record_type) {
+        Some(record_type) => record_type,
+        None => {
+            println!("{} Unsupported record type: {}", "❌".red(), record_type);
+            return Ok(());
+        }
+    };
+
+    // Create resolver
+    let resolver = match create_resolver(server.as_deref(), Some(timeout)) {
+        Ok(resolver) => resolver,
+        Err(e) => {
+            println!("{} {}", "❌".red(), e);
+            return Ok(());
+        }
+    };
+
+    println!("Query: {} {}", domain.bright_cyan(), format!("{:?}", record_type).bright_yellow());
+    
+    if let Some(ref server_ip) = server {
+        println!("Using DNS server: {}", server_ip.bright_magenta());
+    }
+    
+    println!();
+
+    // Perform DNS lookup
+    let start_time = Instant::now();
+    let result = resolver.lookup(&domain, record_type).await;
+    let elapsed = start_time.elapsed();
+
+    match result {
+        Ok(response) => {
+            if response.iter().count() == 0 {
+                println!("{} No records found", "❌".red());
+                return Ok(());
+            }
+
+            println!("{} DNS Records Found:", "✅".green());
+            println!();
+
+            for record in response.iter() {
+                match record {
+                    RData::A(ip) => {
+                        println!("  {} {}", "A".bright_yellow().bold(), ip.to_string().bright_white());
+                    }
+                    RData::AAAA(ip) => {
+                        println!("  {} {}", "AAAA".bright_yellow().bold(), ip.to_string().bright_white());
+                    }
+                    RData::MX(mx) => {
+                        println!("  {} {} {}", 
+                            "MX".bright_yellow().bold(), 
+                            mx.preference().to_string().bright_cyan(),
+                            mx.exchange().to_string().bright_white()
+                        );
+                    }
+                    RData::NS(ns) => {
+                        println!("  {} {}", "NS".bright_yellow().bold(), ns.to_string().bright_white());
+                    }
+                    RData::TXT(txt) => {
+                        for txt_data in txt.iter() {
+                            println!("  {} \"{}\"", 
+                                "TXT".bright_yellow().bold(), 
+                                String::from_utf8_lossy(txt_data).bright_white()
+                            );
+                        }
+                    }
+                    RData::CNAME(cname) => {
+                        println!("  {} {}", "CNAME".bright_yellow().bold(), cname.to_string().bright_white());
+                    }
+                    RData::SOA(soa) => {
+                        println!("  {} {} {} {} {} {} {} {}",
+                            "SOA".bright_yellow().bold(),
+                            soa.mname().to_string().bright_white(),
+                            soa.rname().to_string().bright_cyan(),
+                            soa.serial().to_string().bright_magenta(),
+                            soa.refresh().to_string().bright_green(),
+                            soa.retry().to_string().bright_red(),
+                            soa.expire().to_string().bright_blue(),
+                            soa.minimum().to_string().bright_yellow()
+                        );
+                    }
+                    RData::PTR(ptr) => {
+                        println!("  {} {}", "PTR".bright_yellow().bold(), ptr.to_string().bright_white());
+                    }
+                    _ => {
+                        println!("  {} {}", "OTHER".bright_yellow().bold(), format!("{:?}", record).bright_white());
+                    }
+                }
+            }
+
+            // Additional information
+            println!();
+            println!("{} Query completed in {}", 
+                "⏱️".bright_blue(), 
+                format_duration(elapsed).bright_white()
+            );
+        }
+        Err(e) => {
+            println!("{} DNS lookup failed after {}: {}", "❌".red(), format_duration(elapsed), e.to_string().red());
+            
+            // Provide helpful error messages
+            match e.kind() {
+                trust_dns_resolver::error::ResolveErrorKind::NoRecordsFound { .. } => {
+                    println!("{} No records of type {:?} found for {}", "ℹ️".blue(), record_type, domain);
+                }
+                _ => {
+                    println!("{} This could indicate:", "💡".yellow());
+                    println!("  • Domain doesn't exist");
+                    println!("  • DNS server is unreachable");
+                    println!("  • Network connectivity issues");
+                    println!("  • Firewall blocking DNS requests");
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates a resolver that sends every query to `server`, or uses the
+/// system configuration when no server is given. With `timeout` each query
+/// gets a single try of that length (`attempts = 0`, no retries), so
+/// `--timeout` bounds the whole lookup; without it the resolver's default
+/// timeout and retries apply.
+pub(crate) fn create_resolver(server: Option<&str>, timeout: Option<Duration>) -> Result<TokioAsyncResolver> {
+    let with_timeout = |mut options: ResolverOpts| {
+        if let Some(timeout) = timeout {
+            options.timeout = timeout;
+            // Counts retries, not tries
+            options.attempts = 0;
+        }
+        options
+    };
+
+    match server {
+        Some(server_ip) => {
+            let server_addr = parse_server(server_ip)
+                .map_err(|_| anyhow::anyhow!("Invalid DNS server IP: {}", server_ip))?;
+
+            let mut config = ResolverConfig::new();
+            config.add_name_server(NameServerConfig {
+                socket_addr: server_addr,
+                protocol: trust_dns_resolver::config::Protocol::Udp,
+                tls_dns_name: None,
+                trust_negative_responses: false,
+                bind_addr: None,
+            });
+
+            Ok(TokioAsyncResolver::tokio(config, with_timeout(ResolverOpts::default())))
+        }
+        None => {
+            let (config, options) = trust_dns_resolver::system_conf::read_system_conf()
+                .map_err(|e| anyhow::anyhow!("Failed to create DNS resolver: {}", e))?;
+            Ok(TokioAsyncResolver::tokio(config, with_timeout(options)))
+        }
+    }
+}
+
+/// The server raw queries go to: `server` if given, otherwise the first
+/// system nameserver (raw queries bypass the resolver and need a concrete one).
+pub(crate) fn raw_query_server(server: Option<&str>) -> Result<SocketAddr> {
+    match server {
+        Some(server_ip) => parse_server(server_ip).map_err(|_| anyhow::anyhow!("Invalid DNS server IP: {}", server_ip)),
+        None => system_nameservers()
+            .ok()
+            .and_then(|servers| servers.first().copied())
+            .ok_or_else(|| anyhow::anyhow!("No system nameserver configured, use --server")),
+    }
+}
+
+pub(crate) fn parse_record_type(record_type: &str) -> Option<RecordType> {
+    match record_type.to_uppercase().as_str() {
+        "A" => Some(RecordType::A),
+        "AAAA" => Some(RecordType::AAAA),
+        "MX" => Some(RecordType::MX),
+        "NS" => Some(RecordType::NS),
+        "TXT" => Some(RecordType::TXT),
+        "CNAME" => Some(RecordType::CNAME),
+        "SOA" => Some(RecordType::SOA),
+        "PTR" => Some(RecordType::PTR),
+        _ => None,
+    }
+}
+
+/// Renders record data as a single plain-text line, for tables and files.
+pub(crate) fn format_rdata(rdata: &RData) -> String {
+    match rdata {
+        RData::A(ip) => ip.to_string(),
+        RData::AAAA(ip) => ip.to_string(),
+        RData::MX(mx) => format!("{} {}", mx.preference(), mx.exchange()),
+        RData::NS(ns) => ns.to_string(),
+        RData::TXT(txt) => txt
+            .iter()
+            .map(|data| format!("\"{}\"", String::from_utf8_lossy(data)))
+            .collect::<Vec<_>>()
+            .join(" "),
+        RData::CNAME(cname) => cname.to_string(),
+        RData::SOA(soa) => format!(
+            "{} {} {} {} {} {} {}",
+            soa.mname(),
+            soa.rname(),
+            soa.serial(),
+            soa.refresh(),
+            soa.retry(),
+            soa.expire(),
+            soa.minimum()
+        ),
+        RData::PTR(ptr) => ptr.to_string(),
+        other => other.to_string(),
+    }
+}
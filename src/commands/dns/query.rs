@@ -0,0 +1,249 @@
+use anyhow::Result;
+use colored::*;
+use ipnet::IpNet;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::time::Duration;
+
+use trust_dns_resolver::proto::rr::{DNSClass, RecordType};
+
+use super::message::{display_response, EDNS_CLIENT_SUBNET, EDNS_NSID};
+use super::raw_query_server;
+use crate::network::dns::{op_code_name, send_query, DnsQuery, Transport};
+use crate::utils::format::format_hex_dump;
+
+/// Header bits and EDNS contents of a hand-built query.
+pub struct QueryOptions {
+    pub record_type: String,
+    pub class: String,
+    pub opcode: String,
+    pub recursion_desired: bool,
+    pub checking_disabled: bool,
+    pub dnssec_ok: bool,
+    pub client_subnet: Option<String>,
+    pub nsid: bool,
+    /// Advertised UDP payload size, or `None` to send no OPT record
+    pub payload: Option<u16>,
+    pub tcp: bool,
+    pub show_hex: bool,
+}
+
+pub async fn dns_query_command(
+    name: String,
+    server: Option<String>,
+    options: QueryOptions,
+    timeout: Duration,
+) -> Result<()> {
+    println!("{} {}", "🛠️ DNS QUERY".bright_green().bold(), name.bright_white().bold());
+
+    let record_type = match parse_query_type(&options.record_type) {
+        Some(record_type) => record_type,
+        None => {
+            println!("{} Unknown record type: {}", "❌".red(), options.record_type);
+            return Ok(());
+        }
+    };
+    let class = match parse_class(&options.class) {
+        Some(class) => class,
+        None => {
+            println!("{} Unknown class: {} (use IN, CH, HS, NONE or ANY)", "❌".red(), options.class);
+            return Ok(());
+        }
+    };
+    let opcode = match parse_opcode(&options.opcode) {
+        Some(opcode) => opcode,
+        None => {
+            println!("{} Invalid opcode: {} (use a name or 0-15)", "❌".red(), options.opcode);
+            return Ok(());
+        }
+    };
+    let client_subnet = match options.client_subnet.as_deref().map(parse_subnet).transpose() {
+        Ok(subnet) => subnet,
+        Err(e) => {
+            println!("{} {}", "❌".red(), e);
+            return Ok(());
+        }
+    };
+
+    let server_addr = match raw_query_server(server.as_deref()) {
+        Ok(addr) => addr,
+        Err(e) => {
+            println!("{} {}", "❌".red(), e);
+            return Ok(());
+        }
+    };
+
+    let mut query = DnsQuery::new(&name, record_type)?
+        .query_class(class)
+        .op_code(opcode)
+        .recursion_desired(options.recursion_desired)
+        .checking_disabled(options.checking_disabled)
+        .edns_payload(options.payload)
+        .dnssec_ok(options.dnssec_ok);
+    if options.nsid {
+        query = query.edns_option(EDNS_NSID, Vec::new());
+    }
+    if let Some(ref subnet) = client_subnet {
+        query = query.edns_option(EDNS_CLIENT_SUBNET, client_subnet_option(subnet));
+    }
+
+    let transport = if options.tcp { Transport::Tcp } else { Transport::Udp };
+
+    println!(
+        "Query: {} {} {}",
+        name.bright_cyan(),
+        class.to_string().bright_cyan(),
+        record_type.to_string().bright_yellow()
+    );
+    println!("Server: {} via {}", server_addr.to_string().bright_magenta(), transport.to_string().bright_cyan());
+    println!("Sent: {}", describe_request(&options, opcode, client_subnet.as_ref()).bright_white());
+    println!();
+
+    if options.show_hex {
+        let request = query.to_wire(0)?;
+        println!("{} ({} bytes, ID shown as 0)", "🔢 Request wire format".bright_blue().bold(), request.len());
+        for line in format_hex_dump(&request).lines() {
+            println!("  {}", line.bright_white());
+        }
+        println!();
+    }
+
+    // No TCP retry on truncation: the point is to see exactly what came back
+    let response = match send_query(server_addr, &query, transport, timeout).await {
+        Ok(response) => response,
+        Err(e) => {
+            println!("{} DNS query failed: {}", "❌".red(), e.to_string().red());
+            return Ok(());
+        }
+    };
+
+    display_response(&response, record_type, transport);
+
+    if options.show_hex {
+        println!();
+        println!("{} ({} bytes)", "🔢 Response wire format".bright_blue().bold(), response.wire.len());
+        for line in format_hex_dump(&response.wire).lines() {
+            println!("  {}", line.bright_white());
+        }
+    }
+
+    Ok(())
+}
+
+fn describe_request(options: &QueryOptions, opcode: u8, client_subnet: Option<&IpNet>) -> String {
+    let mut parts = vec![
+        format!("opcode {}", op_code_name(opcode)),
+        format!("RD={}", u8::from(options.recursion_desired)),
+        format!("CD={}", u8::from(options.checking_disabled)),
+    ];
+
+    match options.payload {
+        Some(payload) => {
+            parts.push(format!("EDNS payload {}", payload));
+            parts.push(format!("DO={}", u8::from(options.dnssec_ok)));
+            if options.nsid {
+                parts.push("NSID".to_string());
+            }
+            if let Some(subnet) = client_subnet {
+                parts.push(format!("ECS {}", subnet));
+            }
+        }
+        None => parts.push("no EDNS".to_string()),
+    }
+
+    parts.join(", ")
+}
+
+/// Accepts any mnemonic trust-dns knows, a bare number or the RFC 3597 `TYPEnnn` form.
+pub(super) fn parse_query_type(value: &str) -> Option<RecordType> {
+    let upper = value.trim().to_uppercase();
+    let number = upper.strip_prefix("TYPE").unwrap_or(&upper);
+
+    match number.parse::<u16>() {
+        Ok(code) => Some(RecordType::from(code)),
+        Err(_) => RecordType::from_str(&upper).ok(),
+    }
+}
+
+fn parse_class(value: &str) -> Option<DNSClass> {
+    match value.trim().to_uppercase().as_str() {
+        "CHAOS" => Some(DNSClass::CH),
+        "HESIOD" => Some(DNSClass::HS),
+        other => DNSClass::from_str(other).ok(),
+    }
+}
+
+fn parse_opcode(value: &str) -> Option<u8> {
+    let value = value.trim().to_uppercase();
+    let named = (0..16).find(|code| op_code_name(*code) == value);
+
+    named.or_else(|| value.parse::<u8>().ok().filter(|code| *code < 16))
+}
+
+/// Parses `203.0.113.0/24`; a bare address means its full-length prefix.
+pub(super) fn parse_subnet(value: &str) -> Result<IpNet> {
+    let value = value.trim();
+    if let Ok(net) = value.parse::<IpNet>() {
+        return Ok(net.trunc());
+    }
+
+    value
+        .parse::<IpAddr>()
+        .map(IpNet::from)
+        .map_err(|_| anyhow::anyhow!("Invalid client subnet: {}", value))
+}
+
+/// Encodes an EDNS Client Subnet option (RFC 7871): family, source prefix,
+/// scope prefix 0 and only as many address bytes as the prefix covers.
+pub(super) fn client_subnet_option(subnet: &IpNet) -> Vec<u8> {
+    let (family, address): (u16, Vec<u8>) = match subnet.trunc().addr() {
+        IpAddr::V4(ip) => (1, ip.octets().to_vec()),
+        IpAddr::V6(ip) => (2, ip.octets().to_vec()),
+    };
+    let prefix = subnet.prefix_len();
+
+    let mut data = Vec::with_capacity(4 + address.len());
+    data.extend_from_slice(&family.to_be_bytes());
+    data.push(prefix);
+    data.push(0);
+    data.extend_from_slice(&address[..(prefix as usize).div_ceil(8)]);
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query_type() {
+        assert_eq!(parse_query_type("txt"), Some(RecordType::TXT));
+        assert_eq!(parse_query_type("CAA"), Some(RecordType::CAA));
+        assert_eq!(parse_query_type("TYPE65"), Some(RecordType::HTTPS));
+        assert_eq!(parse_query_type("65534"), Some(RecordType::Unknown(65534)));
+        assert_eq!(parse_query_type("BOGUS"), None);
+    }
+
+    #[test]
+    fn test_parse_class_and_opcode() {
+        assert_eq!(parse_class("ch"), Some(DNSClass::CH));
+        assert_eq!(parse_class("CHAOS"), Some(DNSClass::CH));
+        assert_eq!(parse_class("XX"), None);
+
+        assert_eq!(parse_opcode("status"), Some(2));
+        assert_eq!(parse_opcode("9"), Some(9));
+        assert_eq!(parse_opcode("16"), None);
+    }
+
+    #[test]
+    fn test_client_subnet_option() {
+        let v4 = parse_subnet("203.0.113.77/24").unwrap();
+        assert_eq!(client_subnet_option(&v4), vec![0, 1, 24, 0, 203, 0, 113]);
+
+        let v6 = parse_subnet("2001:db8:abcd::/36").unwrap();
+        assert_eq!(client_subnet_option(&v6), vec![0, 2, 36, 0, 0x20, 0x01, 0x0d, 0xb8, 0xa0]);
+
+        let host = parse_subnet("192.0.2.1").unwrap();
+        assert_eq!(client_subnet_option(&host), vec![0, 1, 32, 0, 192, 0, 2, 1]);
+        assert!(parse_subnet("not-a-subnet").is_err());
+    }
+}
@@ -0,0 +1,343 @@
+use anyhow::Result;
+use colored::*;
+use indicatif::{ProgressBar, ProgressStyle};
+use ipnet::IpNet;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+use trust_dns_resolver::proto::rr::{RData, RecordType};
+use trust_dns_resolver::TokioAsyncResolver;
+
+use super::create_resolver;
+use crate::network::dns::reverse_name;
+
+/// Largest range we are willing to sweep in one go (a /16 for IPv4).
+const MAX_SWEEP_ADDRESSES: u128 = 65536;
+
+struct PtrResult {
+    ip: IpAddr,
+    names: Vec<PtrName>,
+    error: Option<String>,
+}
+
+struct PtrName {
+    name: String,
+    /// Whether the name resolves back to the original address (FCrDNS)
+    forward_confirmed: bool,
+}
+
+/// Returns true when `target` is an IP address or CIDR range rather than a name.
+pub fn is_reverse_target(target: &str) -> bool {
+    target.parse::<IpAddr>().is_ok() || target.parse::<IpNet>().is_ok()
+}
+
+pub async fn dns_reverse_command(target: String, server: Option<String>, concurrency: usize, timeout: Duration) -> Result<()> {
+    println!("{} {}", "🔁 REVERSE DNS".bright_green().bold(), target.bright_white().bold());
+
+    let resolver = match create_resolver(server.as_deref(), Some(timeout)) {
+        Ok(resolver) => Arc::new(resolver),
+        Err(e) => {
+            println!("{} {}", "❌".red(), e);
+            return Ok(());
+        }
+    };
+
+    if let Some(ref server_ip) = server {
+        println!("Using DNS server: {}", server_ip.bright_magenta());
+    }
+
+    if let Ok(ip) = target.parse::<IpAddr>() {
+        println!("Query: {} {}", reverse_name(ip).bright_cyan(), "PTR".bright_yellow());
+        println!();
+
+        let result = lookup_ptr(&resolver, ip).await;
+        display_single(&result);
+        return Ok(());
+    }
+
+    let network = match target.parse::<IpNet>() {
+        Ok(network) => network.trunc(),
+        Err(_) => {
+            println!("{} Invalid IP address or CIDR range: {}", "❌".red(), target);
+            return Ok(());
+        }
+    };
+
+    let size = range_size(&network);
+    if size > MAX_SWEEP_ADDRESSES {
+        println!(
+            "{} Range {} has {} addresses; sweeps are limited to {}",
+            "❌".red(),
+            network,
+            size,
+            MAX_SWEEP_ADDRESSES
+        );
+        return Ok(());
+    }
+
+    sweep(resolver, network, size, concurrency.max(1)).await
+}
+
+async fn sweep(resolver: Arc<TokioAsyncResolver>, network: IpNet, size: u128, concurrency: usize) -> Result<()> {
+    println!("Sweeping {} addresses in {}", size.to_string().bright_white(), network.to_string().bright_yellow());
+    println!();
+
+    let pb = ProgressBar::new(size as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({per_sec}) {msg}")
+            .unwrap()
+            .progress_chars("#>-"),
+    );
+
+    let semaphore = Arc::new(Semaphore::new(concurrency));
+    let mut handles = Vec::new();
+
+    for ip in sweep_addresses(&network) {
+        let permit = semaphore.clone().acquire_owned().await?;
+        let resolver = resolver.clone();
+        let pb = pb.clone();
+
+        handles.push(tokio::spawn(async move {
+            let _permit = permit;
+            let result = lookup_ptr(&resolver, ip).await;
+            pb.inc(1);
+            result
+        }));
+    }
+
+    let mut results = Vec::new();
+    for handle in handles {
+        if let Ok(result) = handle.await {
+            results.push(result);
+        }
+    }
+
+    pb.finish_with_message("Sweep complete");
+    println!();
+
+    let named: Vec<&PtrResult> = results.iter().filter(|result| !result.names.is_empty()).collect();
+    let failed = results.iter().filter(|result| result.error.is_some()).count();
+
+    if named.is_empty() {
+        println!("{} No PTR records found in {}", "❌".red(), network);
+    } else {
+        println!("{} {} addresses have PTR records:", "✅".green(), named.len());
+        println!();
+        for result in &named {
+            for (index, ptr) in result.names.iter().enumerate() {
+                let ip = if index == 0 { result.ip.to_string() } else { String::new() };
+                println!("  {:<39} {} {}", ip.bright_cyan(), fcrdns_marker(ptr.forward_confirmed), ptr.name.bright_white());
+            }
+        }
+    }
+
+    let confirmed = named
+        .iter()
+        .filter(|result| result.names.iter().any(|ptr| ptr.forward_confirmed))
+        .count();
+
+    println!();
+    println!(
+        "{} {} addresses: {} named, {} without PTR, {} lookup errors",
+        "📊".bright_blue(),
+        results.len().to_string().bright_white(),
+        named.len().to_string().bright_green(),
+        (results.len() - named.len() - failed).to_string().bright_yellow(),
+        failed.to_string().bright_red()
+    );
+    if !named.is_empty() {
+        println!(
+            "{} FCrDNS: {} of {} named addresses resolve back to themselves",
+            "🔁".bright_blue(),
+            confirmed.to_string().bright_green(),
+            named.len()
+        );
+    }
+
+    Ok(())
+}
+
+async fn lookup_ptr(resolver: &TokioAsyncResolver, ip: IpAddr) -> PtrResult {
+    let lookup = match resolver.lookup(reverse_name(ip), RecordType::PTR).await {
+        Ok(lookup) => lookup,
+        Err(e) => {
+            let error = match e.kind() {
+                trust_dns_resolver::error::ResolveErrorKind::NoRecordsFound { .. } => None,
+                _ => Some(e.to_string()),
+            };
+            return PtrResult { ip, names: Vec::new(), error };
+        }
+    };
+
+    let mut names = Vec::new();
+    for rdata in lookup.iter() {
+        if let RData::PTR(ptr) = rdata {
+            let name = ptr.to_string();
+            // Ask for the address's own family: lookup_ip stops at the A
+            // records of a dual-stack name
+            let forward_confirmed = match ip {
+                IpAddr::V4(ip) => resolver
+                    .ipv4_lookup(name.as_str())
+                    .await
+                    .is_ok_and(|lookup| lookup.iter().any(|a| a.0 == ip)),
+                IpAddr::V6(ip) => resolver
+                    .ipv6_lookup(name.as_str())
+                    .await
+                    .is_ok_and(|lookup| lookup.iter().any(|aaaa| aaaa.0 == ip)),
+            };
+            names.push(PtrName { name, forward_confirmed });
+        }
+    }
+
+    PtrResult { ip, names, error: None }
+}
+
+fn display_single(result: &PtrResult) {
+    if let Some(ref error) = result.error {
+        println!("{} Reverse lookup failed: {}", "❌".red(), error.red());
+        return;
+    }
+
+    if result.names.is_empty() {
+        println!("{} No PTR records found for {}", "❌".red(), result.ip);
+        return;
+    }
+
+    println!("{} PTR Records Found:", "✅".green());
+    println!();
+    for ptr in &result.names {
+        println!("  {} {} {}", "PTR".bright_yellow().bold(), ptr.name.bright_white(), fcrdns_marker(ptr.forward_confirmed));
+    }
+
+    println!();
+    if result.names.iter().any(|ptr| ptr.forward_confirmed) {
+        println!("{} Forward-confirmed reverse DNS: the name resolves back to {}", "✅".green(), result.ip);
+    } else {
+        println!("{} FCrDNS failed: no PTR name resolves back to {}", "⚠️".yellow(), result.ip);
+        println!("  • Mail servers and some services may reject this address");
+    }
+}
+
+fn fcrdns_marker(confirmed: bool) -> ColoredString {
+    if confirmed {
+        "[FCrDNS ✓]".bright_green()
+    } else {
+        "[FCrDNS ✗]".bright_red()
+    }
+}
+
+fn range_size(network: &IpNet) -> u128 {
+    let host_bits = (network.max_prefix_len() - network.prefix_len()) as u32;
+    if host_bits >= 128 {
+        u128::MAX
+    } else {
+        1u128 << host_bits
+    }
+}
+
+/// Every address in the range, including network and broadcast addresses,
+/// which often carry PTR records for routers.
+fn sweep_addresses(network: &IpNet) -> Vec<IpAddr> {
+    match network {
+        IpNet::V4(net) => {
+            let start = u32::from(net.network());
+            let end = u32::from(net.broadcast());
+            (start..=end).map(|ip| IpAddr::V4(ip.into())).collect()
+        }
+        IpNet::V6(net) => {
+            let start = u128::from(net.network());
+            let end = u128::from(net.broadcast());
+            (start..=end).map(|ip| IpAddr::V6(ip.into())).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_reverse_target() {
+        assert!(is_reverse_target("192.0.2.10"));
+        assert!(is_reverse_target("2001:db8::1"));
+        assert!(is_reverse_target("10.1.2.0/24"));
+        assert!(!is_reverse_target("example.com"));
+    }
+
+    #[test]
+    fn test_sweep_addresses() {
+        let network: IpNet = "10.1.2.4/30".parse().unwrap();
+        let addresses = sweep_addresses(&network);
+
+        assert_eq!(range_size(&network), 4);
+        assert_eq!(addresses.len(), 4);
+        assert_eq!(addresses[0], "10.1.2.4".parse::<IpAddr>().unwrap());
+        assert_eq!(addresses[3], "10.1.2.7".parse::<IpAddr>().unwrap());
+    }
+
+    /// Answers PTR, A and AAAA queries for a dual-stack mail.example.test.
+    async fn dual_stack_server() -> String {
+        use tokio::net::UdpSocket;
+        use trust_dns_resolver::proto::op::{Message, MessageType};
+        use trust_dns_resolver::proto::rr::rdata::{A, AAAA, PTR};
+        use trust_dns_resolver::proto::rr::{Name, Record};
+
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let server = socket.local_addr().unwrap().to_string();
+
+        tokio::spawn(async move {
+            let mut buffer = vec![0u8; 512];
+            loop {
+                let (len, peer) = socket.recv_from(&mut buffer).await.unwrap();
+                let request = Message::from_vec(&buffer[..len]).unwrap();
+                let query = request.queries()[0].clone();
+                let owner = query.name().clone();
+
+                let rdata = match query.query_type() {
+                    RecordType::PTR => Some(RData::PTR(PTR(Name::from_ascii("mail.example.test.").unwrap()))),
+                    RecordType::A => Some(RData::A(A("192.0.2.25".parse().unwrap()))),
+                    RecordType::AAAA => Some(RData::AAAA(AAAA("2001:db8::25".parse().unwrap()))),
+                    _ => None,
+                };
+                let mut response = Message::new();
+                response
+                    .set_id(request.id())
+                    .set_message_type(MessageType::Response)
+                    .set_recursion_available(true)
+                    .add_query(query);
+                if let Some(rdata) = rdata {
+                    response.add_answer(Record::from_rdata(owner, 300, rdata));
+                }
+                socket.send_to(&response.to_vec().unwrap(), peer).await.unwrap();
+            }
+        });
+
+        server
+    }
+
+    #[tokio::test]
+    async fn test_fcrdns_for_both_families() {
+        let server = dual_stack_server().await;
+        let resolver = create_resolver(Some(&server), Some(Duration::from_secs(2))).unwrap();
+
+        for ip in ["2001:db8::25", "192.0.2.25"] {
+            let result = lookup_ptr(&resolver, ip.parse().unwrap()).await;
+            assert_eq!(result.names.len(), 1, "{}", ip);
+            assert_eq!(result.names[0].name, "mail.example.test.");
+            assert!(result.names[0].forward_confirmed, "{} is not forward-confirmed", ip);
+        }
+
+        let result = lookup_ptr(&resolver, "2001:db8::26".parse().unwrap()).await;
+        assert!(!result.names[0].forward_confirmed);
+    }
+
+    #[test]
+    fn test_range_size_ipv6() {
+        let network: IpNet = "2001:db8::/120".parse().unwrap();
+        assert_eq!(range_size(&network), 256);
+        assert_eq!(range_size(&"::/0".parse().unwrap()), u128::MAX);
+    }
+}
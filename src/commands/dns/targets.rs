@@ -0,0 +1,209 @@
+use anyhow::Result;
+use colored::*;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use trust_dns_resolver::proto::rr::{RData, RecordType};
+
+use super::create_resolver;
+use crate::network::dns::{parse_server, system_nameservers};
+use crate::network::resolver::{address_family, resolve_all, AddressFamily};
+
+const DEFAULT_RESOLVERS: &[(&str, &str)] = &[
+    ("8.8.8.8", "Google"),
+    ("1.1.1.1", "Cloudflare"),
+    ("9.9.9.9", "Quad9"),
+    ("208.67.222.222", "OpenDNS"),
+];
+
+pub(super) struct Target {
+    pub label: String,
+    pub server: SocketAddr,
+}
+
+/// Builds the resolver list from the command line and an optional file,
+/// falling back to the system nameservers plus a few public resolvers.
+pub(super) fn resolver_targets(resolvers: &[String], resolvers_file: Option<&str>) -> Result<Vec<Target>> {
+    let mut targets = Vec::new();
+
+    for resolver in resolvers {
+        targets.push(Target {
+            label: resolver.clone(),
+            server: parse_server(resolver)?,
+        });
+    }
+
+    if let Some(path) = resolvers_file {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read resolvers file {}: {}", path, e))?;
+        targets.extend(parse_resolvers_file(&contents)?);
+    }
+
+    if targets.is_empty() {
+        for server in system_nameservers().unwrap_or_default() {
+            targets.push(Target {
+                label: "system".to_string(),
+                server,
+            });
+        }
+        for (address, name) in DEFAULT_RESOLVERS {
+            targets.push(Target {
+                label: name.to_string(),
+                server: parse_server(address)?,
+            });
+        }
+    }
+
+    Ok(targets)
+}
+
+/// Parses one resolver per line as `address [label]`, skipping blank lines
+/// and `#` comments.
+fn parse_resolvers_file(contents: &str) -> Result<Vec<Target>> {
+    let mut targets = Vec::new();
+
+    for line in contents.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut parts = line.split_whitespace();
+        let address = parts.next().unwrap_or("");
+        let label = parts.collect::<Vec<_>>().join(" ");
+
+        targets.push(Target {
+            label: if label.is_empty() { address.to_string() } else { label },
+            server: parse_server(address)?,
+        });
+    }
+
+    Ok(targets)
+}
+
+/// The addresses of a `--server` given as an IP address, `host`, or
+/// `host:port`; a host name yields one target per address.
+pub(super) async fn server_targets(server: &str) -> Result<Vec<Target>> {
+    if let Ok(addr) = parse_server(server) {
+        return Ok(vec![Target {
+            label: server.to_string(),
+            server: addr,
+        }]);
+    }
+
+    let (host, port) = match server.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse().map_err(|_| anyhow::anyhow!("Invalid DNS server address: {}", server))?),
+        None => (server, 53),
+    };
+    if host.is_empty() {
+        return Err(anyhow::anyhow!("Invalid DNS server address: {}", server));
+    }
+    let addresses = resolve_all(host)
+        .await
+        .map_err(|e| anyhow::anyhow!("Could not resolve server {}: {}", host, e))?;
+
+    Ok(addresses
+        .into_iter()
+        .map(|ip| Target {
+            label: host.trim_end_matches('.').to_string(),
+            server: SocketAddr::new(ip, port),
+        })
+        .collect())
+}
+
+/// Finds the zone enclosing `domain` and the addresses of all its NS hosts,
+/// asking `server` (or the system resolver) with `timeout` per query.
+pub(super) async fn authoritative_targets(domain: &str, server: Option<&str>, timeout: Duration) -> Result<(String, Vec<Target>)> {
+    let resolver = create_resolver(server, Some(timeout))?;
+
+    // Walk up the name until we hit a label that owns an SOA record. The
+    // owner must be the candidate itself: for an alias the resolver follows
+    // the CNAME and returns the SOA of the target's zone
+    let labels: Vec<&str> = domain.trim_end_matches('.').split('.').collect();
+    let mut zone = None;
+    for start in 0..labels.len() {
+        let candidate = labels[start..].join(".");
+        if let Ok(lookup) = resolver.lookup(candidate.as_str(), RecordType::SOA).await {
+            let owns_soa = lookup.records().iter().any(|record| {
+                matches!(record.data(), Some(RData::SOA(_)))
+                    && record.name().to_ascii().trim_end_matches('.').eq_ignore_ascii_case(&candidate)
+            });
+            if owns_soa {
+                zone = Some(candidate);
+                break;
+            }
+        }
+    }
+    let zone = zone.ok_or_else(|| anyhow::anyhow!("No SOA record found for {} or its parents", domain))?;
+
+    let ns_lookup = resolver
+        .lookup(zone.as_str(), RecordType::NS)
+        .await
+        .map_err(|e| anyhow::anyhow!("NS lookup for {} failed: {}", zone, e))?;
+
+    // Ask for A and AAAA separately: lookup_ip stops at the A records of a
+    // dual-stack name, which would leave the IPv6 listeners untested
+    let family = address_family();
+    let mut targets = Vec::new();
+    for rdata in ns_lookup.iter() {
+        if let RData::NS(ns) = rdata {
+            let ns_name = ns.to_string();
+            let (v4, v6) = tokio::join!(resolver.ipv4_lookup(ns_name.as_str()), resolver.ipv6_lookup(ns_name.as_str()));
+
+            let mut addresses: Vec<IpAddr> = Vec::new();
+            if let Ok(ref lookup) = v4 {
+                addresses.extend(lookup.iter().map(|a| IpAddr::V4(a.0)));
+            }
+            if let Ok(ref lookup) = v6 {
+                addresses.extend(lookup.iter().map(|aaaa| IpAddr::V6(aaaa.0)));
+            }
+            addresses.retain(|ip| family.allows(ip));
+
+            if addresses.is_empty() {
+                let reason = match (family, v4, v6) {
+                    (AddressFamily::V4, Err(e), _) | (AddressFamily::V6, _, Err(e)) | (AddressFamily::Any, Err(e), Err(_)) => e.to_string(),
+                    _ => "no addresses of the requested family".to_string(),
+                };
+                println!("{} Could not resolve nameserver {}: {}", "⚠️".yellow(), ns_name, reason);
+            }
+            for ip in addresses {
+                targets.push(Target {
+                    label: ns_name.trim_end_matches('.').to_string(),
+                    server: SocketAddr::new(ip, 53),
+                });
+            }
+        }
+    }
+
+    Ok((zone, targets))
+}
+
+pub(super) fn target_label(target: &Target) -> String {
+    if parse_server(&target.label).ok() == Some(target.server) {
+        target.server.to_string()
+    } else {
+        format!("{} ({})", target.label, target.server)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_resolvers_file() {
+        let contents = "# public resolvers\n8.8.8.8 Google DNS\n\n1.1.1.1:53\n[2001:db8::53]:5353 # lab\n";
+        let targets = parse_resolvers_file(contents).unwrap();
+
+        assert_eq!(targets.len(), 3);
+        assert_eq!(targets[0].label, "Google DNS");
+        assert_eq!(targets[0].server, "8.8.8.8:53".parse().unwrap());
+        assert_eq!(targets[1].label, "1.1.1.1:53");
+        assert_eq!(targets[2].server, "[2001:db8::53]:5353".parse().unwrap());
+    }
+
+    #[test]
+    fn test_parse_resolvers_file_invalid_address() {
+        assert!(parse_resolvers_file("not-an-ip\n").is_err());
+    }
+}
@@ -0,0 +1,271 @@
+use anyhow::Result;
+use colored::*;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use trust_dns_resolver::proto::op::ResponseCode;
+use trust_dns_resolver::proto::rr::RecordType;
+
+use super::{format_rdata, parse_record_type, raw_query_server};
+use crate::network::dns::{rcode_name, reverse_name, send_query, DnsQuery, Transport};
+use crate::utils::exit::ExitStatus;
+use crate::utils::format::format_duration;
+
+/// One poll's view of the name.
+#[derive(Debug, Clone, PartialEq)]
+struct Observation {
+    rcode: ResponseCode,
+    /// Every answer record as `TYPE data`, sorted so rotation is not a change
+    answers: Vec<String>,
+    /// Data of the answers with the queried type, for `--expect`
+    values: Vec<String>,
+    ttl: Option<u32>,
+}
+
+impl Observation {
+    fn same_answer(&self, other: &Observation) -> bool {
+        self.rcode == other.rcode && self.answers == other.answers
+    }
+
+    fn summary(&self) -> String {
+        if self.answers.is_empty() {
+            rcode_name(self.rcode)
+        } else {
+            format!("{} {}", rcode_name(self.rcode), self.answers.join(", "))
+        }
+    }
+}
+
+/// Exit status for a watch interrupted before `--expect` matched, as a shell
+/// reports a command killed by SIGINT.
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Re-queries `domain` every `interval` and reports TTLs and answer changes.
+///
+/// With `expect` the watch ends (exit status 0) once the answer matches;
+/// anything that stops it from starting is an error (exit status 1), and
+/// Ctrl-C before a match exits with [`INTERRUPTED_EXIT_CODE`], so a
+/// deployment script can tell "propagated" from "could not check" and
+/// "gave up waiting".
+pub async fn dns_watch_command(
+    domain: String,
+    server: Option<String>,
+    record_type: String,
+    interval: u64,
+    expect: Vec<String>,
+    timeout: Duration,
+) -> Result<()> {
+    println!("{} {}", "👀 DNS WATCH".bright_green().bold(), domain.bright_white().bold());
+
+    let record_type = parse_record_type(&record_type)
+        .ok_or_else(|| anyhow::anyhow!("Unsupported record type: {}", record_type))?;
+    let (domain, record_type) = match domain.parse::<IpAddr>() {
+        Ok(ip) => (reverse_name(ip), RecordType::PTR),
+        Err(_) => (domain, record_type),
+    };
+    let server_addr = raw_query_server(server.as_deref())?;
+    let interval = Duration::from_secs(interval.max(1));
+
+    println!("Query: {} {}", domain.bright_cyan(), format!("{:?}", record_type).bright_yellow());
+    println!("Server: {}", server_addr.to_string().bright_magenta());
+    println!("Interval: {}", format_duration(interval));
+    if !expect.is_empty() {
+        println!("Waiting for: {}", expect.join(", ").bright_cyan());
+    }
+    println!();
+
+    let start_time = Instant::now();
+    let mut ticker = tokio::time::interval(interval);
+    let mut current: Option<Observation> = None;
+    let mut polls = 0u32;
+    let mut changes = 0u32;
+    // One listener for the whole watch: Ctrl-C during a query must not be lost
+    let interrupted = tokio::signal::ctrl_c();
+    tokio::pin!(interrupted);
+
+    loop {
+        let poll = async {
+            ticker.tick().await;
+            let timestamp = chrono::Local::now().format("%H:%M:%S").to_string();
+            (timestamp, observe(server_addr, &domain, record_type, timeout).await)
+        };
+        let (timestamp, observed) = tokio::select! {
+            polled = poll => polled,
+            _ = &mut interrupted => {
+                println!();
+                println!(
+                    "{} Stopped after {}: {} queries, {} changes",
+                    "⏹️".bright_blue(),
+                    format_duration(start_time.elapsed()),
+                    polls,
+                    changes
+                );
+                if !expect.is_empty() {
+                    println!("{} Interrupted before the answer matched {}", "❌".red(), expect.join(", "));
+                    return Err(ExitStatus(INTERRUPTED_EXIT_CODE).into());
+                }
+                return Ok(());
+            }
+        };
+
+        polls += 1;
+        let observation = match observed {
+            Ok(observation) => observation,
+            Err(e) => {
+                // A failed poll says nothing about the answer, so the state is kept
+                println!("[{}] {} {}", timestamp.bright_black(), "✗".bright_red(), e.to_string().red());
+                continue;
+            }
+        };
+
+        let ttl = observation.ttl.map(|ttl| format!("ttl {:>6}", ttl)).unwrap_or_else(|| format!("ttl {:>6}", "-"));
+        match current {
+            Some(ref previous) if previous.same_answer(&observation) => {
+                println!("[{}]   {}  {}", timestamp.bright_black(), ttl.bright_cyan(), observation.summary());
+            }
+            Some(ref previous) => {
+                changes += 1;
+                println!(
+                    "[{}] {} {}  {}",
+                    timestamp.bright_white().bold(),
+                    "⚡".bright_yellow(),
+                    ttl.bright_cyan(),
+                    observation.summary().bright_yellow().bold()
+                );
+                println!("{:13}was: {}", "", previous.summary().bright_black());
+            }
+            None => {
+                println!("[{}]   {}  {}", timestamp.bright_white(), ttl.bright_cyan(), observation.summary().bright_white());
+            }
+        }
+
+        if !expect.is_empty() && matches_expectation(&expect, &observation) {
+            println!();
+            println!(
+                "{} Answer matches the expected value after {} ({} queries, {} changes)",
+                "✅".green(),
+                format_duration(start_time.elapsed()).bright_white(),
+                polls,
+                changes
+            );
+            return Ok(());
+        }
+
+        current = Some(observation);
+    }
+}
+
+async fn observe(server: SocketAddr, domain: &str, record_type: RecordType, timeout: Duration) -> Result<Observation> {
+    let query = DnsQuery::new(domain, record_type)?;
+    let mut response = send_query(server, &query, Transport::Udp, timeout).await?;
+    if response.message.truncated() {
+        response = send_query(server, &query, Transport::Tcp, timeout).await?;
+    }
+
+    let records = response.message.answers();
+    let mut answers: Vec<String> = records
+        .iter()
+        .filter_map(|record| record.data().map(|data| format!("{} {}", record.record_type(), format_rdata(data))))
+        .collect();
+    answers.sort();
+
+    let mut values: Vec<String> = records
+        .iter()
+        .filter(|record| record.record_type() == record_type)
+        .filter_map(|record| record.data().map(format_rdata))
+        .collect();
+    values.sort();
+
+    Ok(Observation {
+        rcode: response.message.response_code(),
+        answers,
+        values,
+        ttl: records.iter().map(|record| record.ttl()).min(),
+    })
+}
+
+/// An rcode name (e.g. NXDOMAIN) matches the response code; otherwise the
+/// expected values must be exactly the records of the queried type, in any
+/// order, ignoring case, quotes and trailing dots.
+fn matches_expectation(expect: &[String], observation: &Observation) -> bool {
+    if let [single] = expect {
+        if is_rcode_name(single) {
+            return rcode_name(observation.rcode).eq_ignore_ascii_case(single.trim());
+        }
+    }
+
+    if observation.rcode != ResponseCode::NoError {
+        return false;
+    }
+
+    let normalize = |value: &str| value.trim().trim_matches('"').trim_end_matches('.').to_ascii_lowercase();
+    let mut expected: Vec<String> = expect.iter().map(|value| normalize(value)).collect();
+    let mut actual: Vec<String> = observation.values.iter().map(|value| normalize(value)).collect();
+    expected.sort();
+    expected.dedup();
+    actual.sort();
+    actual.dedup();
+
+    expected == actual
+}
+
+fn is_rcode_name(value: &str) -> bool {
+    matches!(
+        value.trim().to_uppercase().as_str(),
+        "NOERROR" | "FORMERR" | "SERVFAIL" | "NXDOMAIN" | "NOTIMP" | "REFUSED" | "NOTAUTH"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observation(rcode: ResponseCode, values: &[&str]) -> Observation {
+        Observation {
+            rcode,
+            answers: values.iter().map(|value| format!("A {}", value)).collect(),
+            values: values.iter().map(|value| value.to_string()).collect(),
+            ttl: Some(300),
+        }
+    }
+
+    fn expect(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_matches_expectation_values() {
+        let current = observation(ResponseCode::NoError, &["192.0.2.1", "192.0.2.2"]);
+
+        assert!(matches_expectation(&expect(&["192.0.2.2", "192.0.2.1"]), &current));
+        assert!(!matches_expectation(&expect(&["192.0.2.1"]), &current));
+        assert!(!matches_expectation(&expect(&["192.0.2.1", "192.0.2.2", "192.0.2.3"]), &current));
+
+        let cname = observation(ResponseCode::NoError, &["New.Example.com."]);
+        assert!(matches_expectation(&expect(&["new.example.com"]), &cname));
+
+        let txt = observation(ResponseCode::NoError, &["\"v=spf1 -all\""]);
+        assert!(matches_expectation(&expect(&["v=spf1 -all"]), &txt));
+    }
+
+    #[test]
+    fn test_matches_expectation_rcode() {
+        let gone = observation(ResponseCode::NXDomain, &[]);
+        assert!(matches_expectation(&expect(&["nxdomain"]), &gone));
+        assert!(!matches_expectation(&expect(&["NOERROR"]), &gone));
+        assert!(!matches_expectation(&expect(&["192.0.2.1"]), &gone));
+    }
+
+    #[test]
+    fn test_same_answer_ignores_ttl() {
+        let mut first = observation(ResponseCode::NoError, &["192.0.2.1"]);
+        let second = Observation {
+            ttl: Some(120),
+            ..first.clone()
+        };
+        assert!(first.same_answer(&second));
+
+        first.answers.push("A 192.0.2.9".to_string());
+        assert!(!first.same_answer(&second));
+    }
+}
@@ -0,0 +1,320 @@
+use anyhow::Result;
+use regex::Regex;
+use serde_json::Value;
+use serde_json_path::JsonPath;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use super::HttpResponse;
+use crate::utils::format::format_duration;
+
+/// Assertions on the final response, for cron jobs and deploy gates.
+#[derive(Default)]
+pub(super) struct Expectations {
+    statuses: Vec<RangeInclusive<u16>>,
+    /// As given, for messages
+    statuses_text: String,
+    /// Name and, unless only presence is checked, the exact value
+    headers: Vec<(String, Option<String>)>,
+    body_regex: Option<Regex>,
+    max_time: Option<Duration>,
+    json: Vec<JsonCheck>,
+}
+
+struct JsonCheck {
+    text: String,
+    path_text: String,
+    path: JsonPath,
+    /// `None` when the path only has to match something
+    expected: Option<Value>,
+}
+
+/// The outcome of one assertion.
+pub(super) struct Check {
+    pub passed: bool,
+    pub message: String,
+}
+
+impl Expectations {
+    pub fn parse(
+        statuses: Option<&str>,
+        headers: &[String],
+        body_regex: Option<&str>,
+        max_time: Option<&str>,
+        json: &[String],
+    ) -> Result<Self> {
+        let statuses_text = statuses.unwrap_or_default().to_string();
+        let statuses = statuses
+            .map(|list| list.split(',').map(parse_status).collect::<Result<Vec<_>>>())
+            .transpose()?
+            .unwrap_or_default();
+
+        let headers = headers
+            .iter()
+            .map(|header| match header.split_once(':') {
+                Some((name, value)) => (name.trim().to_string(), Some(value.trim().to_string())),
+                None => (header.trim().to_string(), None),
+            })
+            .collect();
+
+        let body_regex = body_regex
+            .map(|pattern| Regex::new(pattern).map_err(|e| anyhow::anyhow!("Invalid --expect-body-regex: {}", e)))
+            .transpose()?;
+        let max_time = max_time.map(parse_duration).transpose()?;
+        let json = json.iter().map(|check| JsonCheck::parse(check)).collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            statuses,
+            statuses_text,
+            headers,
+            body_regex,
+            max_time,
+            json,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.statuses.is_empty()
+            && self.headers.is_empty()
+            && self.body_regex.is_none()
+            && self.max_time.is_none()
+            && self.json.is_empty()
+    }
+
+    pub fn evaluate(&self, response: &HttpResponse) -> Vec<Check> {
+        let mut checks = Vec::new();
+
+        if !self.statuses.is_empty() {
+            let status = response.status_code;
+            let passed = self.statuses.iter().any(|range| range.contains(&status));
+            let verdict = if passed { "is in" } else { "is not in" };
+            checks.push(Check {
+                passed,
+                message: format!("status {} {} {}", status, verdict, self.statuses_text),
+            });
+        }
+
+        for (name, expected) in &self.headers {
+            let values: Vec<&str> = response
+                .headers
+                .iter()
+                .filter(|(header, _)| header.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.trim())
+                .collect();
+            let check = match expected {
+                _ if values.is_empty() => Check {
+                    passed: false,
+                    message: format!("header {} is missing", name),
+                },
+                None => Check {
+                    passed: true,
+                    message: format!("header {} is present", name),
+                },
+                Some(expected) if values.contains(&expected.as_str()) => Check {
+                    passed: true,
+                    message: format!("header {}: {}", name, expected),
+                },
+                Some(expected) => Check {
+                    passed: false,
+                    message: format!("header {} is '{}', expected '{}'", name, values.join(", "), expected),
+                },
+            };
+            checks.push(check);
+        }
+
+        if let Some(ref regex) = self.body_regex {
+            let passed = regex.is_match(&String::from_utf8_lossy(&response.body));
+            let verdict = if passed { "matches" } else { "does not match" };
+            checks.push(Check {
+                passed,
+                message: format!("body {} /{}/", verdict, regex),
+            });
+        }
+
+        if let Some(max_time) = self.max_time {
+            // Redirects count, it is what a client waits for
+            let elapsed = response.redirects.iter().map(|redirect| redirect.time).sum::<Duration>() + response.timings.total();
+            let passed = elapsed <= max_time;
+            let verdict = if passed { "within" } else { "over" };
+            checks.push(Check {
+                passed,
+                message: format!("took {}, {} --max-time {}", format_duration(elapsed), verdict, format_duration(max_time)),
+            });
+        }
+
+        if !self.json.is_empty() {
+            match serde_json::from_slice::<Value>(&response.body) {
+                Ok(document) => checks.extend(self.json.iter().map(|check| check.evaluate(&document))),
+                Err(e) => checks.push(Check {
+                    passed: false,
+                    message: format!("body is not JSON ({}), cannot check {}", e, self.json[0].text),
+                }),
+            }
+        }
+
+        checks
+    }
+}
+
+impl JsonCheck {
+    /// `$.path` must match a node; `$.path == VALUE` must match one equal to
+    /// VALUE, given as JSON or as a bare string.
+    fn parse(text: &str) -> Result<Self> {
+        let (path, expected) = match split_comparison(text) {
+            Some((path, value)) => {
+                let expected = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+                (path, Some(expected))
+            }
+            None => (text.trim(), None),
+        };
+        let compiled = JsonPath::parse(path).map_err(|e| anyhow::anyhow!("Invalid JSONPath '{}': {}", path, e))?;
+
+        Ok(Self {
+            text: text.trim().to_string(),
+            path_text: path.to_string(),
+            path: compiled,
+            expected,
+        })
+    }
+
+    fn evaluate(&self, document: &Value) -> Check {
+        let nodes = self.path.query(document).all();
+        match self.expected {
+            None => Check {
+                passed: !nodes.is_empty(),
+                message: if nodes.is_empty() {
+                    format!("{} matches nothing", self.text)
+                } else {
+                    format!("{} matches {} node(s)", self.text, nodes.len())
+                },
+            },
+            Some(ref expected) if nodes.contains(&expected) => Check {
+                passed: true,
+                message: self.text.clone(),
+            },
+            Some(ref expected) => {
+                let found = match nodes.as_slice() {
+                    [] => "nothing".to_string(),
+                    [node] => node.to_string(),
+                    nodes => format!("{} nodes, none equal", nodes.len()),
+                };
+                Check {
+                    passed: false,
+                    message: format!("{} is {}, expected {}", self.path_text, found, expected),
+                }
+            }
+        }
+    }
+}
+
+/// Splits `path == value` at the first `==` outside brackets and quotes,
+/// so filters such as `$.items[?@.id == 1]` stay part of the path.
+fn split_comparison(text: &str) -> Option<(&str, &str)> {
+    let mut depth = 0usize;
+    let mut quote = None;
+    let bytes = text.as_bytes();
+
+    for (index, byte) in bytes.iter().enumerate() {
+        match (quote, *byte) {
+            (Some(open), byte) if byte == open => quote = None,
+            (Some(_), _) => {}
+            (None, b'"' | b'\'') => quote = Some(*byte),
+            (None, b'[' | b'(') => depth += 1,
+            (None, b']' | b')') => depth = depth.saturating_sub(1),
+            (None, b'=') if depth == 0 && bytes.get(index + 1) == Some(&b'=') => {
+                return Some((text[..index].trim(), text[index + 2..].trim()));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// `200`, `2xx` or `200-299`.
+fn parse_status(spec: &str) -> Result<RangeInclusive<u16>> {
+    let spec = spec.trim();
+    let invalid = || anyhow::anyhow!("Invalid status in --expect-status: '{}'", spec);
+
+    if let Some(class) = spec.strip_suffix("xx").or_else(|| spec.strip_suffix("XX")) {
+        let class: u16 = class.parse().map_err(|_| invalid())?;
+        if !(1..=5).contains(&class) {
+            return Err(invalid());
+        }
+        return Ok(class * 100..=class * 100 + 99);
+    }
+
+    let (low, high) = spec.split_once('-').unwrap_or((spec, spec));
+    let low: u16 = low.trim().parse().map_err(|_| invalid())?;
+    let high: u16 = high.trim().parse().map_err(|_| invalid())?;
+    if low > high {
+        return Err(invalid());
+    }
+    Ok(low..=high)
+}
+
+/// `500ms`, `2s`, `1.5s`, `1m`; a bare number is seconds.
+fn parse_duration(text: &str) -> Result<Duration> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid --max-time '{}', use e.g. 500ms or 2s", text))?;
+
+    let seconds = match unit.trim() {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        _ => return Err(anyhow::anyhow!("Invalid --max-time '{}', use e.g. 500ms or 2s", text)),
+    };
+    Duration::try_from_secs_f64(seconds)
+        .map_err(|_| anyhow::anyhow!("Invalid --max-time '{}', use e.g. 500ms or 2s", text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status_and_duration() {
+        assert_eq!(parse_status("204").unwrap(), 204..=204);
+        assert_eq!(parse_status("2xx").unwrap(), 200..=299);
+        assert_eq!(parse_status(" 301-308").unwrap(), 301..=308);
+        assert!(parse_status("9xx").is_err());
+        assert!(parse_status("ok").is_err());
+
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("1.5s").unwrap(), Duration::from_millis(1500));
+        assert_eq!(parse_duration("2").unwrap(), Duration::from_secs(2));
+        assert!(parse_duration("fast").is_err());
+        assert!(parse_duration("99999999999999999999").is_err());
+        assert!(parse_duration(&format!("1{}m", "0".repeat(400))).is_err());
+    }
+
+    #[test]
+    fn test_split_comparison() {
+        assert_eq!(split_comparison(r#"$.status == "ok""#), Some(("$.status", r#""ok""#)));
+        assert_eq!(
+            split_comparison("$.items[?@.id == 1].name==widget"),
+            Some(("$.items[?@.id == 1].name", "widget"))
+        );
+        assert_eq!(split_comparison("$.items[0]"), None);
+    }
+
+    #[test]
+    fn test_json_checks() {
+        let document: Value = serde_json::from_str(r#"{"status": "ok", "version": 3, "items": [{"id": 1}, {"id": 2}]}"#).unwrap();
+        let check = |text: &str| JsonCheck::parse(text).unwrap().evaluate(&document);
+
+        assert!(check(r#"$.status == "ok""#).passed);
+        assert!(check("$.status == ok").passed);
+        assert!(check("$.version == 3").passed);
+        assert!(check("$.items[?@.id == 2]").passed);
+        assert!(!check("$.missing").passed);
+
+        let failed = check("$.version == 4");
+        assert!(!failed.passed);
+        assert_eq!(failed.message, "$.version is 3, expected 4");
+        assert!(JsonCheck::parse("status").is_err());
+    }
+}
@@ -0,0 +1,284 @@
+use anyhow::Result;
+use colored::*;
+use ipnet::Ipv6Net;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use trust_dns_resolver::TokioAsyncResolver;
+
+use super::connect::tcp_connect;
+use super::dns::create_resolver;
+use crate::network::resolver::{resolve_hostname, source_address};
+use crate::utils::format::format_duration;
+
+/// RFC 7050: a name that only has A records, so any AAAA for it was made up by DNS64.
+const IPV4_ONLY_NAME: &str = "ipv4only.arpa.";
+const IPV4_ONLY_ADDRESSES: [Ipv4Addr; 2] = [Ipv4Addr::new(192, 0, 0, 170), Ipv4Addr::new(192, 0, 0, 171)];
+
+/// Prefix lengths RFC 6052 allows for embedding an IPv4 address.
+const NAT64_PREFIX_LENGTHS: [u8; 6] = [96, 64, 56, 48, 40, 32];
+
+struct Reachability {
+    synthesized: bool,
+    result: Result<Duration, String>,
+}
+
+pub async fn ipv6_check_command(host: String, port: u16, server: Option<String>, timeout: Duration) -> Result<()> {
+    println!("{} {}", "🌐 IPv6 READINESS CHECK".bright_green().bold(), host.bright_white().bold());
+    println!();
+
+    let resolver = match create_resolver(server.as_deref(), None) {
+        Ok(resolver) => resolver,
+        Err(e) => {
+            println!("{} {}", "❌".red(), e);
+            return Ok(());
+        }
+    };
+
+    // Local connectivity: an IPv6 default route and the address it would use.
+    // The documentation prefix only selects a route, nothing is sent.
+    println!("{}", "🖧 This host".bright_blue().bold());
+    let local_v6 = source_address(&IpAddr::V6("2001:db8::1".parse().unwrap()));
+    let local_v4 = source_address(&IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)));
+    match local_v6 {
+        Some(ip) => println!("  {} IPv6 route, source address {}", "✅".green(), ip.to_string().bright_cyan()),
+        None => println!("  {} No IPv6 route", "❌".red()),
+    }
+    match local_v4 {
+        Some(ip) => println!("  {} IPv4 route, source address {}", "ℹ️".blue(), ip.to_string().bright_cyan()),
+        None => println!("  {} No IPv4 route (IPv6-only)", "ℹ️".blue()),
+    }
+
+    println!();
+    println!("{}", "🔄 DNS64 / NAT64".bright_blue().bold());
+    let prefixes = detect_nat64_prefixes(&resolver).await;
+    if prefixes.is_empty() {
+        println!("  {} No DNS64: {} has no AAAA records through this resolver", "ℹ️".blue(), IPV4_ONLY_NAME);
+    } else {
+        for prefix in &prefixes {
+            let kind = if *prefix == well_known_prefix() { " (well-known prefix)" } else { "" };
+            println!("  {} DNS64 detected, NAT64 prefix {}{}", "✅".green(), prefix.to_string().bright_magenta(), kind);
+        }
+    }
+
+    println!();
+    println!("{}", "📇 Target records".bright_blue().bold());
+    let v4: Vec<Ipv4Addr> = match resolver.ipv4_lookup(host.as_str()).await {
+        Ok(lookup) => lookup.iter().map(|a| a.0).collect(),
+        Err(_) => Vec::new(),
+    };
+    let v6: Vec<Ipv6Addr> = match host.parse::<IpAddr>() {
+        Ok(IpAddr::V6(ip)) => vec![ip],
+        Ok(IpAddr::V4(_)) => Vec::new(),
+        Err(_) => match resolver.ipv6_lookup(host.as_str()).await {
+            Ok(lookup) => lookup.iter().map(|aaaa| aaaa.0).collect(),
+            Err(_) => Vec::new(),
+        },
+    };
+
+    let list = |ips: Vec<String>| if ips.is_empty() { "(none)".to_string() } else { ips.join(", ") };
+    println!("  A:    {}", list(v4.iter().map(|ip| ip.to_string()).collect()).bright_white());
+    for ip in &v6 {
+        if is_synthesized(ip, &prefixes) {
+            println!(
+                "  AAAA: {} {}",
+                ip.to_string().bright_yellow(),
+                format!("synthesized from {}", embedded_ipv4(ip, &prefixes).map(|v4| v4.to_string()).unwrap_or_default())
+                    .bright_yellow()
+            );
+        } else {
+            println!("  AAAA: {} {}", ip.to_string().bright_green(), "native".bright_green());
+        }
+    }
+    if v6.is_empty() {
+        println!("  AAAA: {}", "(none)".bright_red());
+    }
+    if let Ok(preferred) = resolve_hostname(&host).await {
+        println!("  Other netdiag commands connect to {} first", preferred.to_string().bright_cyan());
+    }
+
+    println!();
+    println!("{}", format!("🔌 IPv6 TCP reachability (port {})", port).bright_blue().bold());
+    let mut reachability = Vec::new();
+    if local_v6.is_none() {
+        println!("  {} Skipped: this host has no IPv6 route", "⚠️".yellow());
+    } else if v6.is_empty() {
+        println!("  {} Skipped: no IPv6 address to connect to", "⚠️".yellow());
+    } else {
+        for ip in &v6 {
+            let addr = SocketAddr::new(IpAddr::V6(*ip), port);
+            let start_time = Instant::now();
+            let result = match tcp_connect(addr, timeout).await {
+                Ok(Ok(_)) => Ok(start_time.elapsed()),
+                Ok(Err(e)) => Err(e.to_string()),
+                Err(_) => Err(format!("timed out after {}", format_duration(timeout))),
+            };
+
+            match result {
+                Ok(elapsed) => println!(
+                    "  {} {} connected in {}",
+                    "✅".green(),
+                    addr.to_string().bright_white(),
+                    format_duration(elapsed).bright_green()
+                ),
+                Err(ref e) => println!("  {} {} {}", "❌".red(), addr.to_string().bright_white(), e.red()),
+            }
+
+            reachability.push(Reachability {
+                synthesized: is_synthesized(ip, &prefixes),
+                result,
+            });
+        }
+    }
+
+    println!();
+    println!("{}", "🩺 Verdict".bright_blue().bold());
+    let (icon, verdict) = verdict(local_v6.is_some(), &v6, &prefixes, &reachability);
+    println!("  {} {}", icon, verdict);
+
+    Ok(())
+}
+
+/// Asks for AAAA records of ipv4only.arpa and recovers the prefix each one uses.
+async fn detect_nat64_prefixes(resolver: &TokioAsyncResolver) -> Vec<Ipv6Net> {
+    let Ok(lookup) = resolver.ipv6_lookup(IPV4_ONLY_NAME).await else {
+        return Vec::new();
+    };
+
+    let mut prefixes: Vec<Ipv6Net> = lookup.iter().filter_map(|aaaa| nat64_prefix(&aaaa.0)).collect();
+    prefixes.sort();
+    prefixes.dedup();
+    prefixes
+}
+
+/// Finds the RFC 6052 prefix under which `ip` embeds one of the ipv4only.arpa addresses.
+fn nat64_prefix(ip: &Ipv6Addr) -> Option<Ipv6Net> {
+    NAT64_PREFIX_LENGTHS.iter().find_map(|length| {
+        let embedded = extract_ipv4(ip, *length)?;
+        if !IPV4_ONLY_ADDRESSES.contains(&embedded) {
+            return None;
+        }
+        Ipv6Net::new(*ip, *length).ok().map(|net| net.trunc())
+    })
+}
+
+/// Reads the IPv4 address embedded after a prefix of `length` bits. Bits
+/// 64-71 (the "u" octet) are skipped and must be zero.
+fn extract_ipv4(ip: &Ipv6Addr, length: u8) -> Option<Ipv4Addr> {
+    let bytes = ip.octets();
+    let octets: [u8; 4] = match length {
+        32 => [bytes[4], bytes[5], bytes[6], bytes[7]],
+        40 => [bytes[5], bytes[6], bytes[7], bytes[9]],
+        48 => [bytes[6], bytes[7], bytes[9], bytes[10]],
+        56 => [bytes[7], bytes[9], bytes[10], bytes[11]],
+        64 => [bytes[9], bytes[10], bytes[11], bytes[12]],
+        96 => [bytes[12], bytes[13], bytes[14], bytes[15]],
+        _ => return None,
+    };
+
+    if length < 96 && bytes[8] != 0 {
+        return None;
+    }
+    Some(Ipv4Addr::from(octets))
+}
+
+fn well_known_prefix() -> Ipv6Net {
+    "64:ff9b::/96".parse().unwrap()
+}
+
+fn is_synthesized(ip: &Ipv6Addr, prefixes: &[Ipv6Net]) -> bool {
+    well_known_prefix().contains(ip) || prefixes.iter().any(|prefix| prefix.contains(ip))
+}
+
+fn embedded_ipv4(ip: &Ipv6Addr, prefixes: &[Ipv6Net]) -> Option<Ipv4Addr> {
+    prefixes
+        .iter()
+        .chain(std::iter::once(&well_known_prefix()))
+        .find(|prefix| prefix.contains(ip))
+        .and_then(|prefix| extract_ipv4(ip, prefix.prefix_len()))
+}
+
+fn verdict(
+    has_ipv6: bool,
+    v6: &[Ipv6Addr],
+    prefixes: &[Ipv6Net],
+    reachability: &[Reachability],
+) -> (ColoredString, ColoredString) {
+    let native = v6.iter().any(|ip| !is_synthesized(ip, prefixes));
+    let reachable = |synthesized: bool| {
+        reachability
+            .iter()
+            .any(|probe| probe.synthesized == synthesized && probe.result.is_ok())
+    };
+
+    if !has_ipv6 {
+        return ("⚠️".yellow(), "Unknown: this host has no IPv6 connectivity to test from".bright_yellow());
+    }
+
+    if native {
+        if reachable(false) {
+            ("✅".green(), "READY: native IPv6, reachable over TCP".bright_green().bold())
+        } else {
+            (
+                "❌".red(),
+                "BROKEN: AAAA records are published but not reachable; IPv6-only clients fail and dual-stack clients fall back slowly"
+                    .bright_red()
+                    .bold(),
+            )
+        }
+    } else if !v6.is_empty() {
+        if reachable(true) {
+            (
+                "⚠️".yellow(),
+                "NAT64 ONLY: reachable through NAT64, but the target has no IPv6 of its own".bright_yellow().bold(),
+            )
+        } else {
+            ("❌".red(), "BROKEN: only synthesized AAAA records and the NAT64 path fails".bright_red().bold())
+        }
+    } else if prefixes.is_empty() {
+        (
+            "❌".red(),
+            "NOT READY: no AAAA records and no DNS64, unreachable from IPv6-only networks".bright_red().bold(),
+        )
+    } else {
+        ("❌".red(), "NOT READY: no AAAA records, and DNS64 did not synthesize any".bright_red().bold())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v6(ip: &str) -> Ipv6Addr {
+        ip.parse().unwrap()
+    }
+
+    #[test]
+    fn test_nat64_prefix_well_known() {
+        let prefix = nat64_prefix(&v6("64:ff9b::c000:aa")).unwrap();
+        assert_eq!(prefix, well_known_prefix());
+        assert_eq!(nat64_prefix(&v6("64:ff9b::c000:ab")), Some(well_known_prefix()));
+    }
+
+    #[test]
+    fn test_nat64_prefix_network_specific() {
+        // RFC 6052 section 2.4 layouts for 192.0.0.170
+        assert_eq!(nat64_prefix(&v6("2001:db8:122:344:c0:0:aa00:0")), Some("2001:db8:122:344::/64".parse().unwrap()));
+        assert_eq!(nat64_prefix(&v6("2001:db8:c000:aa::")), Some("2001:db8::/32".parse().unwrap()));
+        assert_eq!(nat64_prefix(&v6("2001:db8:1:c000:0:aa00::")), Some("2001:db8:1::/48".parse().unwrap()));
+        assert_eq!(nat64_prefix(&v6("2001:db8::1")), None);
+    }
+
+    #[test]
+    fn test_is_synthesized() {
+        let prefixes = vec!["2001:db8:64::/96".parse().unwrap()];
+
+        assert!(is_synthesized(&v6("2001:db8:64::c633:6401"), &prefixes));
+        assert!(is_synthesized(&v6("64:ff9b::c633:6401"), &[]));
+        assert!(!is_synthesized(&v6("2001:db8:1::1"), &prefixes));
+        assert_eq!(
+            embedded_ipv4(&v6("2001:db8:64::c633:6401"), &prefixes),
+            Some(Ipv4Addr::new(198, 51, 100, 1))
+        );
+    }
+}
@@ -0,0 +1,581 @@
+use anyhow::Result;
+use colored::*;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use trust_dns_resolver::error::ResolveErrorKind;
+use trust_dns_resolver::proto::rr::{RData, RecordType};
+use trust_dns_resolver::TokioAsyncResolver;
+
+use crate::commands::dns::create_resolver;
+use crate::network::smtp::{probe_smtp, SmtpProbe};
+use crate::utils::format::format_duration;
+
+mod policy;
+mod spf;
+
+use policy::{dkim_key_bits, fetch_sts_policy, parse_tags, StsPolicy};
+use spf::{count_lookups, fetch_spf, SpfRecord, SPF_LOOKUP_LIMIT};
+
+const SMTP_PORT: u16 = 25;
+
+/// Certificates expiring sooner than this are flagged.
+const EXPIRY_WARNING_DAYS: i64 = 14;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+    Info,
+}
+
+struct Check {
+    area: &'static str,
+    status: Status,
+    message: String,
+}
+
+/// Collects graded findings, printing each one as it is recorded.
+#[derive(Default)]
+struct Checks {
+    checks: Vec<Check>,
+}
+
+impl Checks {
+    fn record(&mut self, area: &'static str, status: Status, message: impl Into<String>) {
+        let message = message.into();
+        let icon = match status {
+            Status::Pass => "✅".green(),
+            Status::Warn => "⚠️".yellow(),
+            Status::Fail => "❌".red(),
+            Status::Info => "ℹ️".blue(),
+        };
+        println!("  {} {}", icon, message);
+        self.checks.push(Check { area, status, message });
+    }
+
+    fn count(&self, status: Status) -> usize {
+        self.checks.iter().filter(|check| check.status == status).count()
+    }
+}
+
+struct MxHost {
+    preference: u16,
+    name: String,
+    addresses: Vec<IpAddr>,
+}
+
+pub async fn mail_command(
+    domain: String,
+    server: Option<String>,
+    dkim_selectors: Vec<String>,
+    skip_smtp: bool,
+    helo: String,
+    timeout: Duration,
+) -> Result<()> {
+    println!("{} {}", "📧 MAIL CHECK".bright_green().bold(), domain.bright_white().bold());
+
+    let resolver = match create_resolver(server.as_deref(), None) {
+        Ok(resolver) => resolver,
+        Err(e) => {
+            println!("{} {}", "❌".red(), e);
+            return Ok(());
+        }
+    };
+
+    if let Some(ref server_ip) = server {
+        println!("Using DNS server: {}", server_ip.bright_magenta());
+    }
+
+    let domain = domain.trim_end_matches('.').to_string();
+    let mut checks = Checks::default();
+
+    section("📮 MX records");
+    let mx_hosts = check_mx(&resolver, &domain, &mut checks).await;
+
+    section("🛡️ SPF");
+    check_spf(&resolver, &domain, &mut checks).await;
+
+    section("📜 DMARC");
+    check_dmarc(&resolver, &domain, &mut checks).await;
+
+    section("🔑 DKIM");
+    check_dkim(&resolver, &domain, &dkim_selectors, &mut checks).await;
+
+    section("🔒 MTA-STS / TLS-RPT");
+    let sts_policy = check_mta_sts(&resolver, &domain, &mx_hosts, timeout, &mut checks).await;
+
+    if skip_smtp {
+        println!();
+        println!("{} Skipping SMTP checks (--no-smtp)", "ℹ️".blue());
+    } else if !mx_hosts.is_empty() {
+        section("📨 SMTP");
+        let enforced = sts_policy.as_ref().is_some_and(|policy| policy.mode == "enforce");
+        check_smtp(&mx_hosts, &helo, enforced, timeout, &mut checks).await;
+    }
+
+    display_summary(&checks);
+
+    Ok(())
+}
+
+fn section(title: &str) {
+    println!();
+    println!("{}", title.bright_blue().bold());
+}
+
+async fn check_mx(resolver: &TokioAsyncResolver, domain: &str, checks: &mut Checks) -> Vec<MxHost> {
+    let lookup = match resolver.lookup(domain, RecordType::MX).await {
+        Ok(lookup) => lookup,
+        Err(e) => {
+            match e.kind() {
+                ResolveErrorKind::NoRecordsFound { .. } => checks.record("MX", Status::Fail, "No MX records found"),
+                _ => checks.record("MX", Status::Fail, format!("MX lookup failed: {}", e)),
+            }
+            return Vec::new();
+        }
+    };
+
+    let mut records: Vec<(u16, String)> = lookup
+        .iter()
+        .filter_map(|rdata| match rdata {
+            RData::MX(mx) => Some((mx.preference(), mx.exchange().to_string())),
+            _ => None,
+        })
+        .collect();
+    records.sort();
+
+    // RFC 7505: a single "0 ." record means the domain accepts no mail
+    if records.len() == 1 && records[0].1 == "." {
+        checks.record("MX", Status::Info, "Null MX: this domain does not accept mail");
+        return Vec::new();
+    }
+
+    let mut hosts = Vec::new();
+    for (preference, name) in records {
+        let addresses: Vec<IpAddr> = match resolver.lookup_ip(name.as_str()).await {
+            Ok(ips) => ips.iter().collect(),
+            Err(_) => Vec::new(),
+        };
+
+        let shown = if addresses.is_empty() {
+            "no addresses".bright_red().to_string()
+        } else {
+            addresses.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join(", ").bright_white().to_string()
+        };
+        println!("  {} {} {} → {}", "MX".bright_yellow().bold(), preference.to_string().bright_cyan(), name.bright_white(), shown);
+
+        hosts.push(MxHost { preference, name, addresses });
+    }
+
+    let unresolved: Vec<&str> = hosts.iter().filter(|h| h.addresses.is_empty()).map(|h| h.name.as_str()).collect();
+    if unresolved.is_empty() {
+        checks.record("MX", Status::Pass, format!("{} MX host(s), all resolve", hosts.len()));
+    } else {
+        checks.record("MX", Status::Warn, format!("MX host(s) without addresses: {}", unresolved.join(", ")));
+    }
+    if hosts.len() == 1 {
+        checks.record("MX", Status::Info, "Only one MX host, no fallback if it is down");
+    }
+
+    hosts
+}
+
+async fn check_spf(resolver: &TokioAsyncResolver, domain: &str, checks: &mut Checks) {
+    let text = match fetch_spf(resolver, domain).await {
+        Ok(Some(text)) => text,
+        Ok(None) => {
+            checks.record("SPF", Status::Fail, "No SPF record; anyone can send as this domain");
+            return;
+        }
+        Err(e) => {
+            checks.record("SPF", Status::Fail, e.to_string());
+            return;
+        }
+    };
+
+    println!("  {}", text.bright_white());
+
+    let record = match SpfRecord::parse(&text) {
+        Ok(record) => record,
+        Err(e) => {
+            checks.record("SPF", Status::Fail, format!("Invalid SPF record: {}", e));
+            return;
+        }
+    };
+
+    match (record.all_qualifier(), record.redirect()) {
+        (Some('-'), _) => checks.record("SPF", Status::Pass, "Ends with -all: unlisted senders fail"),
+        (Some('~'), _) => checks.record("SPF", Status::Pass, "Ends with ~all: unlisted senders soft-fail"),
+        (Some('?'), _) => checks.record("SPF", Status::Warn, "Ends with ?all: unlisted senders are neutral"),
+        (Some(_), _) => checks.record("SPF", Status::Fail, "Ends with +all: any server may send as this domain"),
+        (None, Some(target)) => checks.record("SPF", Status::Info, format!("Policy delegated to {}", target)),
+        (None, None) => checks.record("SPF", Status::Warn, "No all mechanism: unlisted senders are neutral"),
+    }
+
+    let count = count_lookups(resolver, &record).await;
+    if count.total > SPF_LOOKUP_LIMIT {
+        checks.record(
+            "SPF",
+            Status::Fail,
+            format!("{} DNS lookups, over the limit of {} (permerror)", count.total, SPF_LOOKUP_LIMIT),
+        );
+    } else {
+        checks.record("SPF", Status::Pass, format!("{} of {} DNS lookups used", count.total, SPF_LOOKUP_LIMIT));
+    }
+    for error in count.errors {
+        checks.record("SPF", Status::Warn, format!("Include problem: {}", error));
+    }
+}
+
+async fn check_dmarc(resolver: &TokioAsyncResolver, domain: &str, checks: &mut Checks) {
+    let name = format!("_dmarc.{}", domain);
+    let record = match lookup_txt(resolver, &name).await {
+        Ok(records) => records.into_iter().find(|text| text.starts_with("v=DMARC1")),
+        Err(e) => {
+            checks.record("DMARC", Status::Fail, format!("DMARC lookup failed: {}", e));
+            return;
+        }
+    };
+
+    let Some(record) = record else {
+        checks.record("DMARC", Status::Fail, format!("No DMARC record at {}", name));
+        return;
+    };
+
+    println!("  {}", record.bright_white());
+    let tags = parse_tags(&record);
+
+    match tags.get("p").map(|p| p.to_ascii_lowercase()).as_deref() {
+        Some("reject") => checks.record("DMARC", Status::Pass, "Policy p=reject"),
+        Some("quarantine") => checks.record("DMARC", Status::Pass, "Policy p=quarantine"),
+        Some("none") => checks.record("DMARC", Status::Warn, "Policy p=none only monitors, nothing is enforced"),
+        Some(other) => checks.record("DMARC", Status::Fail, format!("Invalid policy p={}", other)),
+        None => checks.record("DMARC", Status::Fail, "Record has no p= policy"),
+    }
+
+    if let Some(subdomain_policy) = tags.get("sp") {
+        checks.record("DMARC", Status::Info, format!("Subdomain policy sp={}", subdomain_policy));
+    }
+    if let Some(pct) = tags.get("pct").and_then(|pct| pct.parse::<u8>().ok()).filter(|pct| *pct < 100) {
+        checks.record("DMARC", Status::Warn, format!("Policy only applies to {}% of messages", pct));
+    }
+    match tags.get("rua") {
+        Some(rua) => checks.record("DMARC", Status::Pass, format!("Aggregate reports sent to {}", rua)),
+        None => checks.record("DMARC", Status::Warn, "No rua= address, so no aggregate reports"),
+    }
+}
+
+async fn check_dkim(resolver: &TokioAsyncResolver, domain: &str, selectors: &[String], checks: &mut Checks) {
+    if selectors.is_empty() {
+        checks.record("DKIM", Status::Info, "No selectors given (use --dkim-selector to check keys)");
+        return;
+    }
+
+    for selector in selectors {
+        let name = format!("{}._domainkey.{}", selector, domain);
+        let record = match lookup_txt(resolver, &name).await {
+            Ok(records) => records.into_iter().find(|text| parse_tags(text).contains_key("p")),
+            Err(e) => {
+                checks.record("DKIM", Status::Fail, format!("{}: lookup failed: {}", selector, e));
+                continue;
+            }
+        };
+
+        let Some(record) = record else {
+            checks.record("DKIM", Status::Fail, format!("{}: no DKIM key at {}", selector, name));
+            continue;
+        };
+
+        let tags = parse_tags(&record);
+        let key_type = tags.get("k").map(String::as_str).unwrap_or("rsa");
+        let public_key = tags.get("p").map(String::as_str).unwrap_or("");
+
+        if public_key.is_empty() {
+            checks.record("DKIM", Status::Warn, format!("{}: key has been revoked (empty p=)", selector));
+            continue;
+        }
+
+        match dkim_key_bits(key_type, public_key) {
+            Ok(bits) if key_type.eq_ignore_ascii_case("ed25519") || bits >= 2048 => {
+                checks.record("DKIM", Status::Pass, format!("{}: {} {}-bit key", selector, key_type.to_uppercase(), bits))
+            }
+            Ok(bits) if bits >= 1024 => checks.record(
+                "DKIM",
+                Status::Warn,
+                format!("{}: {} {}-bit key, 2048 bits recommended", selector, key_type.to_uppercase(), bits),
+            ),
+            Ok(bits) => checks.record(
+                "DKIM",
+                Status::Fail,
+                format!("{}: {} {}-bit key is too weak", selector, key_type.to_uppercase(), bits),
+            ),
+            Err(e) => checks.record("DKIM", Status::Fail, format!("{}: {}", selector, e)),
+        }
+
+        if tags.get("t").is_some_and(|flags| flags.split(':').any(|flag| flag.trim() == "y")) {
+            checks.record("DKIM", Status::Warn, format!("{}: in testing mode (t=y)", selector));
+        }
+    }
+}
+
+async fn check_mta_sts(
+    resolver: &TokioAsyncResolver,
+    domain: &str,
+    mx_hosts: &[MxHost],
+    timeout: Duration,
+    checks: &mut Checks,
+) -> Option<StsPolicy> {
+    let sts_record = lookup_txt(resolver, &format!("_mta-sts.{}", domain))
+        .await
+        .ok()
+        .and_then(|records| records.into_iter().find(|text| text.starts_with("v=STSv1")));
+    let tlsrpt_record = lookup_txt(resolver, &format!("_smtp._tls.{}", domain))
+        .await
+        .ok()
+        .and_then(|records| records.into_iter().find(|text| text.starts_with("v=TLSRPTv1")));
+
+    let policy = match sts_record {
+        None => {
+            checks.record("MTA-STS", Status::Warn, "MTA-STS not deployed; senders can be downgraded to plaintext");
+            None
+        }
+        Some(record) => {
+            let id = parse_tags(&record).get("id").cloned().unwrap_or_default();
+            checks.record("MTA-STS", Status::Pass, format!("Record published (id={})", id));
+            fetch_policy(domain, mx_hosts, timeout, checks).await
+        }
+    };
+
+    match tlsrpt_record {
+        Some(record) => {
+            let rua = parse_tags(&record).get("rua").cloned().unwrap_or_default();
+            checks.record("TLS-RPT", Status::Pass, format!("TLS reports sent to {}", rua));
+        }
+        None if policy.is_some() => checks.record("TLS-RPT", Status::Warn, "No TLS-RPT record; delivery failures go unreported"),
+        None => checks.record("TLS-RPT", Status::Info, "No TLS-RPT record"),
+    }
+
+    policy
+}
+
+async fn fetch_policy(domain: &str, mx_hosts: &[MxHost], timeout: Duration, checks: &mut Checks) -> Option<StsPolicy> {
+    let text = match fetch_sts_policy(domain, timeout).await {
+        Ok(text) => text,
+        Err(e) => {
+            checks.record("MTA-STS", Status::Fail, format!("Policy fetch failed: {}", e));
+            return None;
+        }
+    };
+
+    let policy = match StsPolicy::parse(&text) {
+        Ok(policy) => policy,
+        Err(e) => {
+            checks.record("MTA-STS", Status::Fail, format!("Invalid policy: {}", e));
+            return None;
+        }
+    };
+
+    println!(
+        "  Policy: mode {}  mx {}  max_age {}",
+        policy.mode.bright_white(),
+        policy.mx.join(", ").bright_cyan(),
+        policy.max_age.map(|age| age.to_string()).unwrap_or_else(|| "-".to_string())
+    );
+
+    match policy.mode.as_str() {
+        "enforce" => checks.record("MTA-STS", Status::Pass, "Policy mode is enforce"),
+        "testing" => checks.record("MTA-STS", Status::Warn, "Policy mode is testing, failures are only reported"),
+        other => checks.record("MTA-STS", Status::Warn, format!("Policy mode is {}", other)),
+    }
+
+    for host in mx_hosts {
+        if !policy.allows(&host.name) {
+            checks.record("MTA-STS", Status::Fail, format!("{} is not listed in the policy's mx patterns", host.name));
+        }
+    }
+
+    Some(policy)
+}
+
+async fn check_smtp(mx_hosts: &[MxHost], helo: &str, enforced: bool, timeout: Duration, checks: &mut Checks) {
+    // Probe every MX at once, then report in preference order
+    let mut handles = Vec::new();
+    for host in mx_hosts {
+        let address = host.addresses.first().copied();
+        let name = host.name.trim_end_matches('.').to_string();
+        let helo = helo.to_string();
+        handles.push(tokio::spawn(async move {
+            match address {
+                Some(ip) => Some(probe_smtp(SocketAddr::new(ip, SMTP_PORT), &name, &helo, timeout).await),
+                None => None,
+            }
+        }));
+    }
+
+    let mut unreachable = false;
+    for (host, handle) in mx_hosts.iter().zip(handles) {
+        let name = host.name.trim_end_matches('.');
+        println!();
+        println!("  {} {} (preference {})", "▶".bright_blue(), name.bright_white().bold(), host.preference);
+
+        match handle.await.ok().flatten() {
+            None => checks.record("SMTP", Status::Fail, format!("{}: no address to connect to", name)),
+            Some(Err(e)) => {
+                unreachable = true;
+                checks.record("SMTP", Status::Fail, format!("{}: cannot connect on port {}: {}", name, SMTP_PORT, e));
+            }
+            Some(Ok(probe)) => display_probe(name, &probe, enforced, checks),
+        }
+    }
+
+    if unreachable {
+        println!();
+        println!("{} Many networks block outbound port 25; try from a server if every MX fails", "💡".yellow());
+    }
+}
+
+fn display_probe(name: &str, probe: &SmtpProbe, enforced: bool, checks: &mut Checks) {
+    println!("  Banner: {} {}", probe.banner.code.to_string().bright_cyan(), probe.banner.text().bright_white());
+    println!("  Connected in {}", format_duration(probe.connect_time).bright_white());
+    if !probe.extensions.is_empty() {
+        println!("  EHLO: {}", probe.extensions.join(", ").bright_cyan());
+    }
+
+    let info = match probe.starttls {
+        None => {
+            checks.record("SMTP", Status::Fail, format!("{}: STARTTLS not offered, mail is sent in plaintext", name));
+            return;
+        }
+        Some(Err(ref e)) => {
+            checks.record("SMTP", Status::Fail, format!("{}: STARTTLS failed: {}", name, e));
+            return;
+        }
+        Some(Ok(ref info)) => info,
+    };
+
+    checks.record("SMTP", Status::Pass, format!("{}: STARTTLS with {} ({})", name, info.protocol, info.cipher));
+
+    let Some(leaf) = info.certificates.first() else {
+        checks.record("SMTP", Status::Fail, format!("{}: no certificate presented", name));
+        return;
+    };
+
+    println!("  Certificate: {}", leaf.subject.bright_white());
+    println!("  Issuer:      {}", leaf.issuer.bright_white());
+    println!("  Names:       {}", leaf.names.join(", ").bright_cyan());
+    println!(
+        "  Expires:     {} ({} days)",
+        leaf.not_after.format("%Y-%m-%d").to_string().bright_white(),
+        leaf.days_remaining()
+    );
+
+    match info.verification {
+        Ok(()) => checks.record("SMTP", Status::Pass, format!("{}: certificate chain is trusted", name)),
+        // Opportunistic STARTTLS works with any certificate; MTA-STS does not
+        Err(ref reason) if enforced => {
+            checks.record("SMTP", Status::Fail, format!("{}: certificate not trusted ({}), MTA-STS senders will refuse it", name, reason))
+        }
+        Err(ref reason) => checks.record("SMTP", Status::Warn, format!("{}: certificate not trusted ({})", name, reason)),
+    }
+
+    if !leaf.matches_hostname(name) {
+        checks.record("SMTP", Status::Warn, format!("{}: certificate does not cover this host name", name));
+    }
+
+    let days = leaf.days_remaining();
+    if days < 0 {
+        checks.record("SMTP", Status::Fail, format!("{}: certificate expired {} days ago", name, -days));
+    } else if days < EXPIRY_WARNING_DAYS {
+        checks.record("SMTP", Status::Warn, format!("{}: certificate expires in {} days", name, days));
+    }
+}
+
+fn display_summary(checks: &Checks) {
+    let passed = checks.count(Status::Pass);
+    let warnings = checks.count(Status::Warn);
+    let failed = checks.count(Status::Fail);
+    let grade = grade(passed, warnings, failed);
+
+    let grade_text = match grade {
+        'A' | 'B' => grade.to_string().bright_green(),
+        'C' => grade.to_string().bright_yellow(),
+        _ => grade.to_string().bright_red(),
+    };
+
+    println!();
+    println!("{} {}", "📊 MAIL HEALTH: grade".bright_blue().bold(), grade_text.bold());
+    println!(
+        "  {} passed, {} warnings, {} failed",
+        passed.to_string().bright_green(),
+        warnings.to_string().bright_yellow(),
+        failed.to_string().bright_red()
+    );
+
+    let issues: Vec<&Check> = checks
+        .checks
+        .iter()
+        .filter(|check| matches!(check.status, Status::Fail | Status::Warn))
+        .collect();
+    if !issues.is_empty() {
+        println!();
+        println!("{}", "Issues to address:".bright_yellow());
+        for check in issues {
+            let marker = if check.status == Status::Fail { "❌".red() } else { "⚠️".yellow() };
+            println!("  {} [{}] {}", marker, check.area, check.message);
+        }
+    }
+}
+
+/// Warnings count half; any failure caps the grade at C.
+fn grade(passed: usize, warnings: usize, failed: usize) -> char {
+    let total = passed + warnings + failed;
+    if total == 0 {
+        return 'F';
+    }
+
+    let score = (passed as f64 + warnings as f64 * 0.5) / total as f64;
+    let grade = match score {
+        s if s >= 0.9 => 'A',
+        s if s >= 0.75 => 'B',
+        s if s >= 0.6 => 'C',
+        s if s >= 0.4 => 'D',
+        _ => 'F',
+    };
+
+    if failed > 0 {
+        grade.max('C')
+    } else {
+        grade
+    }
+}
+
+/// TXT records at `name`, each with its character-strings joined. A name
+/// without TXT records gives an empty list rather than an error.
+async fn lookup_txt(resolver: &TokioAsyncResolver, name: &str) -> Result<Vec<String>> {
+    match resolver.txt_lookup(name).await {
+        Ok(lookup) => Ok(lookup
+            .iter()
+            .map(|txt| txt.iter().map(|data| String::from_utf8_lossy(data)).collect::<String>())
+            .collect()),
+        Err(e) => match e.kind() {
+            ResolveErrorKind::NoRecordsFound { .. } => Ok(Vec::new()),
+            _ => Err(anyhow::anyhow!("{}", e)),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grade() {
+        assert_eq!(grade(10, 0, 0), 'A');
+        assert_eq!(grade(6, 4, 0), 'B');
+        assert_eq!(grade(9, 0, 1), 'C');
+        assert_eq!(grade(2, 0, 8), 'F');
+        assert_eq!(grade(0, 0, 0), 'F');
+    }
+}
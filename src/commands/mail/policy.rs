@@ -0,0 +1,209 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use x509_parser::prelude::FromDer;
+
+use crate::commands::http::DEFAULT_USER_AGENT;
+use crate::network::tls::handshake;
+
+/// Largest MTA-STS policy we accept (RFC 8461 suggests 64 KiB).
+const MAX_POLICY_SIZE: u64 = 64 * 1024;
+/// Largest status line and header block we read ahead of the policy.
+const MAX_HEADER_SIZE: u64 = 16 * 1024;
+
+/// Parses "tag=value; tag=value" records (DMARC, DKIM, MTA-STS, TLS-RPT).
+/// Tag names are lowercased; values keep their case.
+pub(super) fn parse_tags(record: &str) -> HashMap<String, String> {
+    record
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .map(|(tag, value)| (tag.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect()
+}
+
+/// Size in bits of a DKIM public key from the record's `p=` tag.
+pub(super) fn dkim_key_bits(key_type: &str, public_key: &str) -> Result<usize> {
+    if key_type.eq_ignore_ascii_case("ed25519") {
+        return Ok(256);
+    }
+
+    let compact: String = public_key.split_whitespace().collect();
+    let der = data_encoding::BASE64
+        .decode(compact.as_bytes())
+        .map_err(|e| anyhow::anyhow!("Public key is not valid base64: {}", e))?;
+
+    let (_, spki) = x509_parser::x509::SubjectPublicKeyInfo::from_der(&der)
+        .map_err(|e| anyhow::anyhow!("Public key is not a valid key: {}", e))?;
+    let key = spki
+        .parsed()
+        .map_err(|e| anyhow::anyhow!("Unsupported public key: {}", e))?;
+
+    Ok(key.key_size())
+}
+
+pub(super) struct StsPolicy {
+    pub mode: String,
+    pub mx: Vec<String>,
+    pub max_age: Option<u64>,
+}
+
+impl StsPolicy {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut version = None;
+        let mut mode = None;
+        let mut mx = Vec::new();
+        let mut max_age = None;
+
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim().to_string();
+            match key.trim() {
+                "version" => version = Some(value),
+                "mode" => mode = Some(value),
+                "mx" => mx.push(value),
+                "max_age" => max_age = value.parse().ok(),
+                _ => {}
+            }
+        }
+
+        if version.as_deref() != Some("STSv1") {
+            return Err(anyhow::anyhow!("Policy is missing \"version: STSv1\""));
+        }
+        let mode = mode.ok_or_else(|| anyhow::anyhow!("Policy has no mode"))?;
+
+        Ok(Self { mode, mx, max_age })
+    }
+
+    /// Whether an MX host name is covered by one of the policy's `mx` patterns.
+    pub fn allows(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        self.mx.iter().any(|pattern| {
+            let pattern = pattern.trim_end_matches('.').to_ascii_lowercase();
+            match pattern.strip_prefix("*.") {
+                Some(suffix) => host
+                    .split_once('.')
+                    .is_some_and(|(label, rest)| !label.is_empty() && rest == suffix),
+                None => pattern == host,
+            }
+        })
+    }
+}
+
+/// Fetches https://mta-sts.<domain>/.well-known/mta-sts.txt. The certificate
+/// must be valid, as senders will refuse the policy otherwise.
+pub(super) async fn fetch_sts_policy(domain: &str, timeout: Duration) -> Result<String> {
+    let host = format!("mta-sts.{}", domain.trim_end_matches('.'));
+
+    tokio::time::timeout(timeout, async {
+        let stream = TcpStream::connect((host.as_str(), 443))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect to {}: {}", host, e))?;
+        let (mut stream, _) = handshake(stream, &host, true, &[]).await?;
+
+        // HTTP/1.0 keeps the response unchunked and closes when done
+        let request = format!(
+            "GET /.well-known/mta-sts.txt HTTP/1.0\r\nHost: {}\r\nUser-Agent: {}\r\n\r\n",
+            host, DEFAULT_USER_AGENT
+        );
+        stream.write_all(request.as_bytes()).await?;
+
+        read_policy_response(stream, &host).await
+    })
+    .await
+    .map_err(|_| anyhow::anyhow!("Timed out fetching the policy from {}", host))?
+}
+
+/// Reads an HTTP/1.0 response to the end and returns the policy body,
+/// rejecting a non-200 status or a body over [`MAX_POLICY_SIZE`].
+async fn read_policy_response<S: AsyncRead + Unpin>(stream: S, host: &str) -> Result<String> {
+    // Room for the status line and headers on top of the largest policy;
+    // reading one byte past it shows whether the response was cut short
+    let limit = MAX_HEADER_SIZE + MAX_POLICY_SIZE + 1;
+    let mut response = Vec::new();
+    match stream.take(limit).read_to_end(&mut response).await {
+        Ok(_) => {}
+        // Many TLS servers close without a close_notify alert; what arrived is still the response
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    let split = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(|| anyhow::anyhow!("Malformed HTTP response from {}", host))?;
+    let head = String::from_utf8_lossy(&response[..split]);
+    let status = head.split_whitespace().nth(1).unwrap_or("");
+    if status != "200" {
+        return Err(anyhow::anyhow!("{} returned HTTP {}", host, status));
+    }
+
+    let body = &response[split + 4..];
+    if body.len() as u64 > MAX_POLICY_SIZE || response.len() as u64 == limit {
+        return Err(anyhow::anyhow!("The policy from {} is larger than {} KiB", host, MAX_POLICY_SIZE / 1024));
+    }
+
+    Ok(String::from_utf8_lossy(body).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tags() {
+        let tags = parse_tags("v=DMARC1; p=reject; rua=mailto:dmarc@example.com;pct=50");
+
+        assert_eq!(tags.get("v").map(String::as_str), Some("DMARC1"));
+        assert_eq!(tags.get("p").map(String::as_str), Some("reject"));
+        assert_eq!(tags.get("rua").map(String::as_str), Some("mailto:dmarc@example.com"));
+        assert_eq!(tags.get("pct").map(String::as_str), Some("50"));
+    }
+
+    #[test]
+    fn test_sts_policy() {
+        let policy =
+            StsPolicy::parse("version: STSv1\nmode: enforce\nmx: mail.example.com\nmx: *.example.net\nmax_age: 86400\n")
+                .unwrap();
+
+        assert_eq!(policy.mode, "enforce");
+        assert_eq!(policy.max_age, Some(86400));
+        assert!(policy.allows("mail.example.com."));
+        assert!(policy.allows("mx1.example.net"));
+        assert!(!policy.allows("example.net"));
+        assert!(!policy.allows("backup.example.com"));
+    }
+
+    #[test]
+    fn test_sts_policy_requires_version() {
+        assert!(StsPolicy::parse("mode: enforce\nmx: mail.example.com\n").is_err());
+    }
+
+    #[test]
+    fn test_dkim_key_bits_invalid() {
+        assert!(dkim_key_bits("rsa", "not base64!").is_err());
+        assert_eq!(dkim_key_bits("ed25519", "anything").unwrap(), 256);
+    }
+
+    #[tokio::test]
+    async fn test_read_policy_response() {
+        let response = b"HTTP/1.0 200 OK\r\nContent-Type: text/plain\r\n\r\nversion: STSv1\nmode: testing\n";
+        let body = read_policy_response(&response[..], "mta-sts.example.com").await.unwrap();
+        assert_eq!(body, "version: STSv1\nmode: testing\n");
+
+        let missing = b"HTTP/1.0 404 Not Found\r\n\r\n";
+        assert!(read_policy_response(&missing[..], "mta-sts.example.com").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_policy_response_rejects_oversized_policy() {
+        let mut response = b"HTTP/1.0 200 OK\r\n\r\n".to_vec();
+        response.resize(response.len() + MAX_POLICY_SIZE as usize + 1, b'#');
+        let error = read_policy_response(&response[..], "mta-sts.example.com").await.unwrap_err();
+        assert!(error.to_string().contains("larger than 64 KiB"));
+    }
+}
@@ -0,0 +1,207 @@
+use anyhow::Result;
+use std::future::Future;
+use std::pin::Pin;
+
+use trust_dns_resolver::TokioAsyncResolver;
+
+use super::lookup_txt;
+
+/// RFC 7208 section 4.6.4: evaluating a record may cost at most 10 DNS lookups.
+pub(super) const SPF_LOOKUP_LIMIT: usize = 10;
+
+/// Stop following includes well past the limit, in case of loops.
+const MAX_FOLLOWED: usize = 30;
+
+#[derive(Debug, PartialEq)]
+pub(super) enum SpfTerm {
+    Mechanism {
+        qualifier: char,
+        name: String,
+        value: Option<String>,
+    },
+    Modifier {
+        name: String,
+        value: String,
+    },
+}
+
+pub(super) struct SpfRecord {
+    pub terms: Vec<SpfTerm>,
+}
+
+impl SpfRecord {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut parts = text.split_whitespace();
+        if !parts.next().is_some_and(|version| version.eq_ignore_ascii_case("v=spf1")) {
+            return Err(anyhow::anyhow!("Record does not start with v=spf1"));
+        }
+
+        let mut terms = Vec::new();
+        for part in parts {
+            // Modifiers use '=', mechanisms use ':' or '/' (or nothing)
+            let name_end = part.find([':', '/', '=']).unwrap_or(part.len());
+            if part[name_end..].starts_with('=') {
+                terms.push(SpfTerm::Modifier {
+                    name: part[..name_end].to_ascii_lowercase(),
+                    value: part[name_end + 1..].to_string(),
+                });
+                continue;
+            }
+
+            let (qualifier, mechanism) = match part.chars().next() {
+                Some(q @ ('+' | '-' | '~' | '?')) => (q, &part[1..]),
+                _ => ('+', part),
+            };
+            let (name, value) = match mechanism.split_once(':') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (mechanism, None),
+            };
+            // Drop any CIDR suffix from the name ("a/24", "mx/24")
+            let name = name.split('/').next().unwrap_or(name).to_ascii_lowercase();
+
+            match name.as_str() {
+                "all" | "include" | "a" | "mx" | "ptr" | "ip4" | "ip6" | "exists" => {}
+                other => return Err(anyhow::anyhow!("Unknown mechanism: {}", other)),
+            }
+
+            terms.push(SpfTerm::Mechanism { qualifier, name, value });
+        }
+
+        Ok(Self { terms })
+    }
+
+    /// Qualifier of the final `all` mechanism, e.g. '-' for "-all".
+    pub fn all_qualifier(&self) -> Option<char> {
+        self.terms.iter().find_map(|term| match term {
+            SpfTerm::Mechanism { qualifier, name, .. } if name == "all" => Some(*qualifier),
+            _ => None,
+        })
+    }
+
+    pub fn redirect(&self) -> Option<&str> {
+        self.terms.iter().find_map(|term| match term {
+            SpfTerm::Modifier { name, value } if name == "redirect" => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Terms that cost a DNS lookup when the record is evaluated.
+    pub fn lookup_terms(&self) -> usize {
+        self.terms
+            .iter()
+            .filter(|term| match term {
+                SpfTerm::Mechanism { name, .. } => matches!(name.as_str(), "include" | "a" | "mx" | "ptr" | "exists"),
+                SpfTerm::Modifier { name, .. } => name == "redirect",
+            })
+            .count()
+    }
+
+    /// Domains whose SPF records are pulled in by `include:` and `redirect=`.
+    pub fn referenced_domains(&self) -> Vec<&str> {
+        let includes = self.terms.iter().filter_map(|term| match term {
+            SpfTerm::Mechanism { name, value: Some(value), .. } if name == "include" => Some(value.as_str()),
+            _ => None,
+        });
+        includes.chain(self.redirect()).collect()
+    }
+}
+
+/// Returns the single `v=spf1` record published at `domain`; more than one
+/// is itself an error.
+pub(super) async fn fetch_spf(resolver: &TokioAsyncResolver, domain: &str) -> Result<Option<String>> {
+    let records: Vec<String> = lookup_txt(resolver, domain)
+        .await?
+        .into_iter()
+        .filter(|text| text.to_ascii_lowercase().starts_with("v=spf1"))
+        .collect();
+
+    match records.len() {
+        0 => Ok(None),
+        1 => Ok(records.into_iter().next()),
+        count => Err(anyhow::anyhow!("{} SPF records published, only one is allowed", count)),
+    }
+}
+
+pub(super) struct LookupCount {
+    pub total: usize,
+    /// Problems found in included records (missing, invalid, unresolvable)
+    pub errors: Vec<String>,
+}
+
+/// Counts the DNS lookups needed to evaluate `record`, following every
+/// include and redirect.
+pub(super) async fn count_lookups(resolver: &TokioAsyncResolver, record: &SpfRecord) -> LookupCount {
+    let mut count = LookupCount {
+        total: 0,
+        errors: Vec::new(),
+    };
+    let mut followed = 0;
+    count_recursive(resolver, record, &mut count, &mut followed).await;
+    count
+}
+
+fn count_recursive<'a>(
+    resolver: &'a TokioAsyncResolver,
+    record: &'a SpfRecord,
+    count: &'a mut LookupCount,
+    followed: &'a mut usize,
+) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
+    Box::pin(async move {
+        count.total += record.lookup_terms();
+
+        for domain in record.referenced_domains() {
+            *followed += 1;
+            if *followed > MAX_FOLLOWED {
+                count.errors.push("too many nested includes, stopped counting".to_string());
+                return;
+            }
+            // Macro-expanded domains depend on the message being checked
+            if domain.contains('%') {
+                continue;
+            }
+
+            match fetch_spf(resolver, domain).await {
+                Ok(Some(text)) => match SpfRecord::parse(&text) {
+                    Ok(included) => count_recursive(resolver, &included, count, followed).await,
+                    Err(e) => count.errors.push(format!("{}: {}", domain, e)),
+                },
+                Ok(None) => count.errors.push(format!("{}: no SPF record", domain)),
+                Err(e) => count.errors.push(format!("{}: {}", domain, e)),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spf() {
+        let record = SpfRecord::parse("v=spf1 ip4:192.0.2.0/24 a/24 mx include:_spf.example.net ~all").unwrap();
+
+        assert_eq!(record.terms.len(), 5);
+        assert_eq!(
+            record.terms[1],
+            SpfTerm::Mechanism { qualifier: '+', name: "a".to_string(), value: None }
+        );
+        assert_eq!(record.all_qualifier(), Some('~'));
+        assert_eq!(record.lookup_terms(), 3);
+        assert_eq!(record.referenced_domains(), vec!["_spf.example.net"]);
+    }
+
+    #[test]
+    fn test_parse_spf_redirect() {
+        let record = SpfRecord::parse("v=spf1 redirect=_spf.example.com").unwrap();
+
+        assert_eq!(record.all_qualifier(), None);
+        assert_eq!(record.redirect(), Some("_spf.example.com"));
+        assert_eq!(record.lookup_terms(), 1);
+    }
+
+    #[test]
+    fn test_parse_spf_invalid() {
+        assert!(SpfRecord::parse("v=spf2 -all").is_err());
+        assert!(SpfRecord::parse("v=spf1 bogus:example.com -all").is_err());
+    }
+}
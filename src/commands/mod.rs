@@ -2,14 +2,23 @@ pub mod ping;
 pub mod scan;
 pub mod dns;
 pub mod http;
+pub mod ipv6;
+pub mod mail;
+pub mod tls;
 pub mod trace;
 pub mod connect;
 pub mod report;
 
 pub use ping::ping_command;
 pub use scan::scan_command;
-pub use dns::dns_command;
-pub use http::http_command;
+pub use dns::{
+    dns_axfr_command, dns_batch_command, dns_bench_command, dns_command, dns_compare_command, dns_diagnose_local_command, dns_dnsbl_command, dns_ecs_command, dns_edns_probe_command, dns_query_command, dns_reverse_command, dns_verbose_command, dns_watch_command,
+    is_reverse_target, QueryOptions,
+};
+pub use http::{http_command, HttpOptions, HttpVersion};
+pub use ipv6::ipv6_check_command;
+pub use mail::mail_command;
+pub use tls::tls_command;
 pub use trace::trace_command;
 pub use connect::connect_command;
 pub use report::report_command;
\ No newline at end of file
@@ -1,29 +1,35 @@
 use anyhow::Result;
 use colored::*;
+use std::net::IpAddr;
 use std::time::{Duration, Instant};
 use tokio::time;
 
 use crate::network::icmp::IcmpPinger;
-use crate::network::resolver::resolve_hostname;
+use crate::network::resolver::resolve_target;
 use crate::utils::format::format_duration;
 
-pub async fn ping_command(host: String, count: u32, timeout: Duration, _size: usize) -> Result<()> {
+pub async fn ping_command(host: String, count: u32, timeout: Duration, _size: usize, all_addresses: bool) -> Result<()> {
     println!("{} {}", "🏓 PING".bright_green().bold(), host.bright_white().bold());
     
-    // Resolve hostname to IP
-    let ip = match resolve_hostname(&host).await {
-        Ok(ip) => {
-            if ip.to_string() != host {
-                println!("Resolved {} to {}", host.bright_cyan(), ip.to_string().bright_yellow());
-            }
-            ip
-        }
-        Err(e) => {
-            println!("{} Failed to resolve hostname: {}", "❌".red(), e);
-            return Ok(());
-        }
+    // Resolve hostname to every address, most preferred first
+    let Some(addresses) = resolve_target(&host).await else {
+        return Ok(());
     };
 
+    if !all_addresses {
+        return ping_address(addresses[0], count, timeout).await;
+    }
+
+    for ip in addresses {
+        println!();
+        println!("{} {}", "▶".bright_blue(), ip.to_string().bright_white().bold());
+        ping_address(ip, count, timeout).await?;
+    }
+
+    Ok(())
+}
+
+async fn ping_address(ip: IpAddr, count: u32, timeout: Duration) -> Result<()> {
     let pinger = IcmpPinger::new(ip)?;
     let mut successful_pings = 0;
     let mut total_time = Duration::ZERO;
@@ -5,7 +5,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Duration;
 
-use crate::network::resolver::resolve_hostname;
+use super::http::fetch_status;
+use crate::network::resolver::{resolve_all, resolve_target};
 use crate::utils::ports::parse_port_range;
 
 #[derive(Serialize, Deserialize)]
@@ -13,6 +14,8 @@ struct NetworkReport {
     timestamp: DateTime<Utc>,
     target_host: String,
     target_ip: String,
+    /// Every resolved address, in the order connections are attempted
+    target_addresses: Vec<String>,
     tests: HashMap<String, TestResult>,
     summary: ReportSummary,
 }
@@ -38,24 +41,16 @@ pub async fn report_command(host: String, output: Option<String>, detailed_scan:
     println!("Generating comprehensive network diagnostic report...");
     println!();
 
-    // Resolve hostname
-    let ip = match resolve_hostname(&host).await {
-        Ok(ip) => {
-            if ip.to_string() != host {
-                println!("Resolved {} to {}", host.bright_cyan(), ip.to_string().bright_yellow());
-            }
-            ip
-        }
-        Err(e) => {
-            println!("{} Failed to resolve hostname: {}", "❌".red(), e);
-            return Ok(());
-        }
+    // Resolve hostname to every address, most preferred first
+    let Some(addresses) = resolve_target(&host).await else {
+        return Ok(());
     };
 
     let mut report = NetworkReport {
         timestamp: Utc::now(),
         target_host: host.clone(),
-        target_ip: ip.to_string(),
+        target_ip: addresses[0].to_string(),
+        target_addresses: addresses.iter().map(|ip| ip.to_string()).collect(),
         tests: HashMap::new(),
         summary: ReportSummary {
             total_tests: 0,
@@ -70,9 +65,12 @@ pub async fn report_command(host: String, output: Option<String>, detailed_scan:
 
     // Test 1: Basic connectivity (ping-like)
     println!("1. {} Basic connectivity test", "🏓".bright_blue());
-    let ping_result = test_basic_connectivity(&ip).await;
+    let (ping_result, reachable_ip) = test_basic_connectivity(&addresses).await;
     add_test_result(&mut report, "basic_connectivity", ping_result);
 
+    // Later tests use the address that answered, or the preferred one
+    let ip = reachable_ip.unwrap_or(addresses[0]);
+
     // Test 2: DNS resolution
     println!("2. {} DNS resolution test", "🌐".bright_blue());
     let dns_result = test_dns_resolution(&host).await;
@@ -121,56 +119,61 @@ pub async fn report_command(host: String, output: Option<String>, detailed_scan:
     Ok(())
 }
 
-async fn test_basic_connectivity(ip: &std::net::IpAddr) -> TestResult {
+/// Tries each address in preference order, returning the first that accepts
+/// a TCP connection on port 80 or 443.
+async fn test_basic_connectivity(addresses: &[std::net::IpAddr]) -> (TestResult, Option<std::net::IpAddr>) {
     let start = std::time::Instant::now();
-    
-    // Simple TCP connection test to a common port
-    match tokio::time::timeout(
-        Duration::from_secs(5),
-        tokio::net::TcpStream::connect((ip.clone(), 80))
-    ).await {
-        Ok(Ok(_)) => TestResult {
-            success: true,
-            details: "Host is reachable via TCP".to_string(),
-            duration_ms: start.elapsed().as_millis() as u64,
-            error: None,
-        },
-        Ok(Err(_)) => {
-            // Try port 443 if 80 fails
+    let mut timed_out = false;
+
+    for ip in addresses {
+        for port in [80, 443] {
             match tokio::time::timeout(
                 Duration::from_secs(5),
-                tokio::net::TcpStream::connect((ip.clone(), 443))
+                tokio::net::TcpStream::connect((*ip, port))
             ).await {
-                Ok(Ok(_)) => TestResult {
-                    success: true,
-                    details: "Host is reachable via TCP (port 443)".to_string(),
-                    duration_ms: start.elapsed().as_millis() as u64,
-                    error: None,
-                },
-                _ => TestResult {
-                    success: false,
-                    details: "Host is not reachable".to_string(),
-                    duration_ms: start.elapsed().as_millis() as u64,
-                    error: Some("Connection refused on common ports".to_string()),
+                Ok(Ok(_)) => {
+                    let result = TestResult {
+                        success: true,
+                        details: format!("Host is reachable via TCP at {} (port {})", ip, port),
+                        duration_ms: start.elapsed().as_millis() as u64,
+                        error: None,
+                    };
+                    return (result, Some(*ip));
                 }
+                Ok(Err(_)) => {}
+                Err(_) => timed_out = true,
             }
-        },
-        Err(_) => TestResult {
+        }
+    }
+
+    let result = if timed_out {
+        TestResult {
             success: false,
             details: "Connection timeout".to_string(),
             duration_ms: start.elapsed().as_millis() as u64,
-            error: Some("Timeout after 5 seconds".to_string()),
+            error: Some(format!("Timeout after 5 seconds on {} address(es)", addresses.len())),
         }
-    }
+    } else {
+        TestResult {
+            success: false,
+            details: "Host is not reachable".to_string(),
+            duration_ms: start.elapsed().as_millis() as u64,
+            error: Some("Connection refused on common ports".to_string()),
+        }
+    };
+    (result, None)
 }
 
 async fn test_dns_resolution(host: &str) -> TestResult {
     let start = std::time::Instant::now();
     
-    match resolve_hostname(host).await {
-        Ok(ip) => TestResult {
+    match resolve_all(host).await {
+        Ok(addresses) => TestResult {
             success: true,
-            details: format!("Resolved to {}", ip),
+            details: format!(
+                "Resolved to {}",
+                addresses.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join(", ")
+            ),
             duration_ms: start.elapsed().as_millis() as u64,
             error: None,
         },
@@ -201,7 +204,7 @@ async fn test_port_connectivity(ip: &std::net::IpAddr, ports_str: &str) -> TestR
     for port in ports.into_iter().take(100) { // Limit to first 100 ports for performance
         if let Ok(Ok(_)) = tokio::time::timeout(
             Duration::from_millis(1000),
-            tokio::net::TcpStream::connect((ip.clone(), port))
+            tokio::net::TcpStream::connect((*ip, port))
         ).await {
             open_ports.push(port);
         }
@@ -241,9 +244,15 @@ async fn test_http_connectivity(host: &str) -> TestResult {
     }
 }
 
-async fn test_simple_http(_url: &str) -> Result<TestResult> {
-    // Simplified HTTP test - just check if we can connect to port 80 or 443
-    Err(anyhow::anyhow!("HTTP test not implemented"))
+async fn test_simple_http(url: &str) -> Result<TestResult> {
+    let (status_code, response_time) = fetch_status(url, Duration::from_secs(10)).await?;
+
+    Ok(TestResult {
+        success: true,
+        details: format!("{} answered with HTTP {}", url, status_code),
+        duration_ms: response_time,
+        error: None,
+    })
 }
 
 fn add_test_result(report: &mut NetworkReport, test_name: &str, result: TestResult) {
@@ -257,6 +266,9 @@ fn display_report(report: &NetworkReport) {
     println!("{}", "📊 DIAGNOSTIC REPORT SUMMARY".bright_blue().bold());
     println!("═══════════════════════════════════════════");
     println!("Target: {} ({})", report.target_host.bright_white(), report.target_ip.bright_yellow());
+    if report.target_addresses.len() > 1 {
+        println!("Addresses: {}", report.target_addresses.join(", ").bright_yellow());
+    }
     println!("Timestamp: {}", report.timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string().bright_cyan());
     println!();
     
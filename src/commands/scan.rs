@@ -2,14 +2,14 @@ use anyhow::Result;
 use colored::*;
 use indicatif::{ProgressBar, ProgressStyle};
 use std::collections::HashSet;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::net::TcpStream;
 use tokio::sync::Semaphore;
 use tokio::time;
 
-use crate::network::resolver::resolve_hostname;
+use crate::network::resolver::resolve_target;
 use crate::utils::ports::{get_common_ports, parse_port_range};
 
 pub async fn scan_command(
@@ -17,21 +17,13 @@ pub async fn scan_command(
     ports: String,
     timeout: Duration,
     concurrency: usize,
+    all_addresses: bool,
 ) -> Result<()> {
     println!("{} {}", "🔍 PORT SCAN".bright_green().bold(), host.bright_white().bold());
 
-    // Resolve hostname to IP
-    let ip = match resolve_hostname(&host).await {
-        Ok(ip) => {
-            if ip.to_string() != host {
-                println!("Resolved {} to {}", host.bright_cyan(), ip.to_string().bright_yellow());
-            }
-            ip
-        }
-        Err(e) => {
-            println!("{} Failed to resolve hostname: {}", "❌".red(), e);
-            return Ok(());
-        }
+    // Resolve hostname to every address, most preferred first
+    let Some(addresses) = resolve_target(&host).await else {
+        return Ok(());
     };
 
     // Determine ports to scan
@@ -54,6 +46,16 @@ pub async fn scan_command(
         }
     };
 
+    let targets = if all_addresses { addresses } else { vec![addresses[0]] };
+    for ip in targets {
+        println!();
+        scan_address(ip, &port_list, timeout, concurrency).await?;
+    }
+
+    Ok(())
+}
+
+async fn scan_address(ip: IpAddr, port_list: &[u16], timeout: Duration, concurrency: usize) -> Result<()> {
     println!("Scanning {} ports on {}", port_list.len(), ip.to_string().bright_yellow());
     println!();
 
@@ -71,11 +73,10 @@ pub async fn scan_command(
     let open_ports = Arc::new(tokio::sync::Mutex::new(HashSet::new()));
     let total_ports = port_list.len();
 
-    for port in &port_list {
+    for port in port_list {
         let permit = semaphore.clone().acquire_owned().await?;
         let pb = pb.clone();
         let open_ports = open_ports.clone();
-        let ip = ip;
         let port = *port; // Clone the port value to avoid lifetime issues
 
         let handle = tokio::spawn(async move {
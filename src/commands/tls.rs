@@ -0,0 +1,412 @@
+use anyhow::Result;
+use colored::*;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use super::connect::tcp_connect;
+use crate::network::resolver::resolve_hostname;
+use crate::network::tls::{handshake, CertificateSummary, TlsInfo};
+use crate::utils::format::format_duration;
+
+const EXPIRY_WARNING_DAYS: i64 = 14;
+
+/// Protocols offered through ALPN, so the report shows what the server prefers.
+const OFFERED_ALPN: [&str; 2] = ["h2", "http/1.1"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Severity {
+    Pass,
+    Info,
+    Warn,
+    Fail,
+}
+
+struct Finding {
+    severity: Severity,
+    message: String,
+}
+
+impl Finding {
+    fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Finding {
+            severity,
+            message: message.into(),
+        }
+    }
+}
+
+pub async fn tls_command(target: String, sni: Option<String>, timeout: Duration) -> Result<()> {
+    println!("{} {}", "🔒 TLS INSPECTION".bright_green().bold(), target.bright_white().bold());
+
+    let (host, port) = match parse_target(&target) {
+        Ok(target) => target,
+        Err(e) => {
+            println!("{} {}", "❌".red(), e);
+            return Ok(());
+        }
+    };
+    let server_name = sni.unwrap_or_else(|| host.clone());
+
+    let ip = match resolve_hostname(&host).await {
+        Ok(ip) => ip,
+        Err(e) => {
+            println!("{} Failed to resolve {}: {}", "❌".red(), host, e);
+            return Ok(());
+        }
+    };
+    let addr = SocketAddr::new(ip, port);
+    println!("Connecting to {} (SNI {})...", addr.to_string().bright_cyan(), server_name.bright_cyan());
+    println!();
+
+    let stream = match tcp_connect(addr, timeout).await {
+        Ok(Ok(stream)) => stream,
+        Ok(Err(e)) => {
+            println!("{} Connection failed: {}", "❌".red(), e.to_string().red());
+            return Ok(());
+        }
+        Err(_) => {
+            println!("{} Connection timed out after {}", "❌".red(), format_duration(timeout));
+            return Ok(());
+        }
+    };
+
+    // Never abort on an invalid chain: inspecting it is the point
+    let start_time = Instant::now();
+    let info = match tokio::time::timeout(timeout, handshake(stream, &server_name, false, &OFFERED_ALPN)).await {
+        Ok(Ok((_, info))) => info,
+        Ok(Err(e)) => {
+            println!("{} {}", "❌".red(), e.to_string().red());
+            return Ok(());
+        }
+        Err(_) => {
+            println!("{} TLS handshake timed out after {}", "❌".red(), format_duration(timeout));
+            return Ok(());
+        }
+    };
+    let elapsed = start_time.elapsed();
+
+    display_tls_info(&info, &server_name);
+    println!();
+    println!("Handshake completed in {}", format_duration(elapsed).bright_cyan());
+
+    Ok(())
+}
+
+/// Prints the negotiated session, the certificate chain and any problems found.
+pub(crate) fn display_tls_info(info: &TlsInfo, hostname: &str) {
+    println!("{}", "🤝 Handshake".bright_blue().bold());
+    println!("  Protocol:     {}", info.protocol.bright_green());
+    println!("  Cipher:       {}", info.cipher.bright_white());
+    if let Some(ref group) = info.key_exchange {
+        println!("  Key exchange: {}", group.bright_white());
+    }
+    match info.alpn {
+        Some(ref protocol) => println!("  ALPN:         {}", protocol.bright_cyan()),
+        None => println!("  ALPN:         {}", "none negotiated".bright_black()),
+    }
+    if info.ocsp_response_len > 0 {
+        println!("  OCSP staple:  {} ({} bytes)", "yes".bright_green(), info.ocsp_response_len);
+    } else {
+        println!("  OCSP staple:  {}", "no".bright_yellow());
+    }
+
+    println!();
+    println!("{}", format!("📜 Certificate chain ({} sent)", info.certificates.len()).bright_blue().bold());
+    for (index, cert) in info.certificates.iter().enumerate() {
+        display_certificate(index, cert);
+    }
+
+    println!();
+    println!("{}", "🩺 Checks".bright_blue().bold());
+    for finding in check_session(info, hostname) {
+        let icon = match finding.severity {
+            Severity::Pass => "✅".green(),
+            Severity::Info => "ℹ️".blue(),
+            Severity::Warn => "⚠️".yellow(),
+            Severity::Fail => "❌".red(),
+        };
+        println!("  {} {}", icon, finding.message);
+    }
+}
+
+fn display_certificate(index: usize, cert: &CertificateSummary) {
+    let role = if index == 0 {
+        "leaf"
+    } else if cert.is_self_signed() {
+        "root"
+    } else {
+        "intermediate"
+    };
+    let days = cert.days_remaining();
+    let days_text = if days < 0 {
+        format!("expired {} days ago", -days).bright_red()
+    } else if days < EXPIRY_WARNING_DAYS {
+        format!("{} days left", days).bright_yellow()
+    } else {
+        format!("{} days left", days).bright_green()
+    };
+
+    println!("  {} {} {}", format!("#{}", index + 1).bright_magenta(), cert.subject.bright_white().bold(), format!("({})", role).bright_black());
+    println!("     Issuer:    {}", cert.issuer);
+    if !cert.names.is_empty() {
+        println!("     SANs:      {}", cert.names.join(", ").bright_cyan());
+    }
+    println!("     Key:       {}", cert.key);
+    println!("     Signature: {}", cert.signature_algorithm);
+    println!(
+        "     Valid:     {} → {} ({})",
+        cert.not_before.format("%Y-%m-%d"),
+        cert.not_after.format("%Y-%m-%d"),
+        days_text
+    );
+}
+
+fn check_session(info: &TlsInfo, hostname: &str) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    match info.verification {
+        Ok(()) => findings.push(Finding::new(Severity::Pass, "Chain is trusted by the system roots")),
+        Err(ref reason) => findings.push(Finding::new(Severity::Fail, format!("Chain is not trusted: {}", reason))),
+    }
+
+    let Some(leaf) = info.certificates.first() else {
+        findings.push(Finding::new(Severity::Fail, "No certificate was presented"));
+        return findings;
+    };
+
+    if leaf.matches_hostname(hostname) {
+        findings.push(Finding::new(Severity::Pass, format!("Certificate covers {}", hostname)));
+    } else {
+        findings.push(Finding::new(Severity::Fail, format!("Certificate does not cover {}", hostname)));
+    }
+
+    let days = leaf.days_remaining();
+    if !leaf.is_valid_now() && days >= 0 {
+        findings.push(Finding::new(Severity::Fail, "Certificate is not valid yet"));
+    } else if days < 0 {
+        findings.push(Finding::new(Severity::Fail, format!("Certificate expired {} days ago", -days)));
+    } else if days < EXPIRY_WARNING_DAYS {
+        findings.push(Finding::new(Severity::Warn, format!("Certificate expires in {} days", days)));
+    } else {
+        findings.push(Finding::new(Severity::Pass, format!("Certificate valid for {} more days", days)));
+    }
+
+    findings.extend(check_chain(&info.certificates, &info.verification));
+
+    if info.ocsp_response_len == 0 {
+        findings.push(Finding::new(Severity::Info, "No OCSP response stapled; clients must ask the CA themselves"));
+    }
+
+    findings
+}
+
+/// Looks for the usual chain mistakes: certificates out of order, a missing
+/// intermediate, extra or unrelated certificates and expired CA certificates.
+fn check_chain(certs: &[CertificateSummary], verification: &Result<(), String>) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    for (index, cert) in certs.iter().enumerate().skip(1) {
+        if !cert.is_valid_now() {
+            findings.push(Finding::new(
+                Severity::Fail,
+                format!("Certificate #{} ({}) is outside its validity period", index + 1, cert.subject),
+            ));
+        }
+    }
+
+    let mut in_order = true;
+    for (index, pair) in certs.windows(2).enumerate() {
+        let (cert, next) = (&pair[0], &pair[1]);
+        if cert.is_self_signed() {
+            in_order = false;
+            findings.push(Finding::new(
+                Severity::Warn,
+                format!("Certificate #{} follows a self-signed certificate and is never used", index + 2),
+            ));
+        } else if cert.issuer != next.subject {
+            in_order = false;
+            match certs.iter().position(|other| other.subject == cert.issuer) {
+                Some(position) => findings.push(Finding::new(
+                    Severity::Warn,
+                    format!(
+                        "Wrong order: the issuer of #{} is sent as #{}, it should come right after it",
+                        index + 1,
+                        position + 1
+                    ),
+                )),
+                // The issuer is not sent, which only matters when nothing
+                // else in the chain needs the next certificate either
+                None if !issued_any(certs, index + 1) => findings.push(Finding::new(
+                    Severity::Warn,
+                    format!("Certificate #{} did not issue #{} and does not belong in the chain", index + 2, index + 1),
+                )),
+                None => {}
+            }
+        }
+    }
+
+    // The issuer of the last certificate sent must come from the trust store
+    let Some(last) = certs.last() else {
+        return findings;
+    };
+    let issuer_sent = certs.iter().any(|cert| cert.subject == last.issuer);
+
+    if last.is_self_signed() {
+        if certs.len() == 1 {
+            findings.push(Finding::new(Severity::Warn, "Self-signed certificate"));
+        } else {
+            findings.push(Finding::new(Severity::Info, "The root certificate is sent too; clients ignore it"));
+        }
+    } else if !issuer_sent && is_unknown_issuer(verification) {
+        findings.push(Finding::new(
+            Severity::Fail,
+            format!("Missing intermediate: nothing sent is signed by {}", last.issuer),
+        ));
+    } else if in_order {
+        findings.push(Finding::new(Severity::Pass, "Chain is complete and in order"));
+    }
+
+    findings
+}
+
+/// Whether certificate `index` issued any other certificate in the chain.
+fn issued_any(certs: &[CertificateSummary], index: usize) -> bool {
+    certs
+        .iter()
+        .enumerate()
+        .any(|(other, cert)| other != index && cert.issuer == certs[index].subject)
+}
+
+fn is_unknown_issuer(verification: &Result<(), String>) -> bool {
+    matches!(verification, Err(reason) if reason.contains("UnknownIssuer"))
+}
+
+/// Splits `host[:port]`, `[v6]:port` or a bare IPv6 address; the port defaults to 443.
+fn parse_target(target: &str) -> Result<(String, u16)> {
+    let target = target.trim();
+
+    if let Some(rest) = target.strip_prefix('[') {
+        let (host, after) = rest
+            .split_once(']')
+            .ok_or_else(|| anyhow::anyhow!("Invalid target: {}", target))?;
+        let port = match after.strip_prefix(':') {
+            Some(port) => port.parse().map_err(|_| anyhow::anyhow!("Invalid port: {}", port))?,
+            None if after.is_empty() => 443,
+            None => return Err(anyhow::anyhow!("Invalid target: {}", target)),
+        };
+        return Ok((host.to_string(), port));
+    }
+
+    if target.parse::<IpAddr>().is_ok() {
+        return Ok((target.to_string(), 443));
+    }
+
+    match target.rsplit_once(':') {
+        Some((host, port)) => Ok((host.to_string(), port.parse().map_err(|_| anyhow::anyhow!("Invalid port: {}", port))?)),
+        None => Ok((target.to_string(), 443)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn cert(subject: &str, issuer: &str) -> CertificateSummary {
+        CertificateSummary {
+            subject: subject.to_string(),
+            issuer: issuer.to_string(),
+            names: Vec::new(),
+            key: "RSA 2048 bits".to_string(),
+            signature_algorithm: "sha256WithRSAEncryption".to_string(),
+            not_before: Utc::now() - chrono::Duration::days(30),
+            not_after: Utc::now() + chrono::Duration::days(60),
+        }
+    }
+
+    fn severities(findings: &[Finding]) -> Vec<Severity> {
+        findings.iter().map(|finding| finding.severity).collect()
+    }
+
+    #[test]
+    fn test_check_chain_complete() {
+        let chain = vec![cert("CN=www", "CN=Intermediate"), cert("CN=Intermediate", "CN=Root")];
+        assert_eq!(severities(&check_chain(&chain, &Ok(()))), vec![Severity::Pass]);
+    }
+
+    #[test]
+    fn test_check_chain_wrong_order() {
+        let chain = vec![
+            cert("CN=www", "CN=Intermediate"),
+            cert("CN=Cross", "CN=Root"),
+            cert("CN=Intermediate", "CN=Cross"),
+        ];
+        let findings = check_chain(&chain, &Ok(()));
+
+        assert_eq!(severities(&findings), vec![Severity::Warn]);
+        assert!(findings[0].message.contains("Wrong order: the issuer of #1 is sent as #3"));
+    }
+
+    #[test]
+    fn test_check_chain_unrelated_certificate() {
+        let chain = vec![cert("CN=www", "CN=Intermediate"), cert("CN=Unrelated", "CN=Other")];
+        let findings = check_chain(&chain, &Ok(()));
+
+        assert_eq!(severities(&findings), vec![Severity::Warn]);
+        assert!(findings[0].message.contains("Certificate #2 did not issue #1 and does not belong in the chain"));
+    }
+
+    #[test]
+    fn test_check_chain_missing_intermediate() {
+        let chain = vec![cert("CN=www", "CN=Intermediate")];
+        let unknown = Err("invalid peer certificate: UnknownIssuer".to_string());
+
+        let findings = check_chain(&chain, &unknown);
+        assert_eq!(severities(&findings), vec![Severity::Fail]);
+        assert!(findings[0].message.contains("Missing intermediate"));
+
+        // Trusted anyway: the issuer is a root in the store
+        assert_eq!(severities(&check_chain(&chain, &Ok(()))), vec![Severity::Pass]);
+    }
+
+    #[test]
+    fn test_check_chain_self_signed_and_expired() {
+        let single = vec![cert("CN=box", "CN=box")];
+        assert_eq!(severities(&check_chain(&single, &Ok(()))), vec![Severity::Warn]);
+
+        let mut expired = cert("CN=Intermediate", "CN=Root");
+        expired.not_after = Utc::now() - chrono::Duration::days(1);
+        let chain = vec![cert("CN=www", "CN=Intermediate"), expired, cert("CN=Root", "CN=Root")];
+        assert_eq!(severities(&check_chain(&chain, &Ok(()))), vec![Severity::Fail, Severity::Info]);
+    }
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(parse_target("example.com").unwrap(), ("example.com".to_string(), 443));
+        assert_eq!(parse_target("example.com:8443").unwrap(), ("example.com".to_string(), 8443));
+        assert_eq!(parse_target("[2001:db8::1]:993").unwrap(), ("2001:db8::1".to_string(), 993));
+        assert_eq!(parse_target("2001:db8::1").unwrap(), ("2001:db8::1".to_string(), 443));
+        assert!(parse_target("example.com:https").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_handshake_reports_chain() {
+        let acceptor = crate::network::tls::self_signed_acceptor(&["localhost"], &["h2"]);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let _ = acceptor.accept(socket).await;
+        });
+
+        let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let (_, info) = handshake(stream, "localhost", false, &OFFERED_ALPN).await.unwrap();
+        let findings = check_session(&info, "localhost");
+
+        assert_eq!(info.alpn.as_deref(), Some("h2"));
+        assert_eq!(info.certificates.len(), 1);
+        assert_eq!(findings[0].severity, Severity::Fail);
+        assert!(findings.iter().any(|finding| finding.message == "Certificate covers localhost"));
+        assert!(findings.iter().any(|finding| finding.message == "Self-signed certificate"));
+    }
+}
@@ -3,26 +3,26 @@ use colored::*;
 use std::net::IpAddr;
 use std::time::Duration;
 
-use crate::network::resolver::resolve_hostname;
+use crate::network::resolver::resolve_target;
 use crate::network::traceroute::Traceroute;
 
-pub async fn trace_command(host: String, max_hops: u32, timeout: Duration) -> Result<()> {
+pub async fn trace_command(host: String, max_hops: u32, timeout: Duration, all_addresses: bool) -> Result<()> {
     println!("{} {}", "🛣️ TRACEROUTE".bright_green().bold(), host.bright_white().bold());
 
-    // Resolve hostname to IP
-    let target_ip = match resolve_hostname(&host).await {
-        Ok(ip) => {
-            if ip.to_string() != host {
-                println!("Resolved {} to {}", host.bright_cyan(), ip.to_string().bright_yellow());
-            }
-            ip
-        }
-        Err(e) => {
-            println!("{} Failed to resolve hostname: {}", "❌".red(), e);
-            return Ok(());
-        }
+    // Resolve hostname to every address, most preferred first
+    let Some(addresses) = resolve_target(&host).await else {
+        return Ok(());
     };
 
+    let targets = if all_addresses { addresses } else { vec![addresses[0]] };
+    for target_ip in targets {
+        trace_address(target_ip, max_hops, timeout).await?;
+    }
+
+    Ok(())
+}
+
+async fn trace_address(target_ip: IpAddr, max_hops: u32, timeout: Duration) -> Result<()> {
     println!("Tracing route to {} with maximum {} hops", 
         target_ip.to_string().bright_yellow(), 
         max_hops.to_string().bright_cyan()
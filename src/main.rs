@@ -8,6 +8,8 @@ mod network;
 mod utils;
 
 use commands::*;
+use network::resolver::{set_address_family, AddressFamily};
+use utils::exit::ExitStatus;
 
 #[derive(Parser)]
 #[command(name = "netdiag")]
@@ -16,9 +18,16 @@ use commands::*;
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+    /// Only use IPv4 addresses
+    #[arg(short = '4', long = "ipv4", global = true, conflicts_with = "ipv6")]
+    pub ipv4: bool,
+    /// Only use IPv6 addresses
+    #[arg(short = '6', long = "ipv6", global = true)]
+    pub ipv6: bool,
 }
 
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
 pub enum Commands {
     /// Test basic connectivity to a host
     Ping {
@@ -33,6 +42,9 @@ pub enum Commands {
         /// Packet size in bytes
         #[arg(short = 's', long, default_value = "64")]
         size: usize,
+        /// Run against every resolved address, not just the preferred one
+        #[arg(short = 'a', long)]
+        all_addresses: bool,
     },
     /// Scan ports on a target host
     Scan {
@@ -47,17 +59,88 @@ pub enum Commands {
         /// Number of concurrent connections
         #[arg(short = 'c', long, default_value = "100")]
         concurrency: usize,
+        /// Run against every resolved address, not just the preferred one
+        #[arg(short = 'a', long)]
+        all_addresses: bool,
     },
     /// Perform DNS resolution
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Dns {
+        #[command(subcommand)]
+        action: Option<DnsAction>,
         /// Domain name to resolve
-        domain: String,
+        #[arg(required_unless_present_any = ["batch", "diagnose_local", "dnsbl"])]
+        domain: Option<String>,
         /// DNS server to use (optional)
         #[arg(short = 's', long)]
         server: Option<String>,
         /// Record type (A, AAAA, MX, NS, TXT, etc.)
         #[arg(short = 't', long, default_value = "A")]
         record_type: String,
+        /// Send the query to several resolvers at once and compare the answers
+        #[arg(long)]
+        compare: bool,
+        /// Resolvers to compare (e.g., 8.8.8.8,1.1.1.1:53)
+        #[arg(long = "resolver", value_delimiter = ',', requires = "compare")]
+        resolvers: Vec<String>,
+        /// File listing resolvers to compare, one "address [label]" per line
+        #[arg(long, requires = "compare")]
+        resolvers_file: Option<String>,
+        /// Compare the zone's authoritative nameservers and their SOA serials (found through --server, if given)
+        #[arg(long, requires = "compare", conflicts_with_all = ["resolvers", "resolvers_file"])]
+        authoritative: bool,
+        /// Reverse lookup of an IP address, or a PTR sweep of a CIDR range
+        #[arg(short = 'x', long, conflicts_with_all = ["compare", "verbose"])]
+        reverse: bool,
+        /// Number of concurrent lookups for reverse sweeps, batches and client subnet sweeps
+        #[arg(short = 'c', long, default_value = "50")]
+        concurrency: usize,
+        /// Resolve every "name [type]" line of a file
+        #[arg(long, conflicts_with_all = ["domain", "compare", "reverse", "verbose"])]
+        batch: Option<String>,
+        /// Check the local resolver setup (resolv.conf, hosts, nsswitch) and,
+        /// given a domain, how it is looked up
+        #[arg(long, conflicts_with_all = ["compare", "reverse", "verbose", "batch", "server"])]
+        diagnose_local: bool,
+        /// Probe EDNS UDP payload sizes (512, 1232, 4096), truncation and TCP fallback
+        #[arg(long, conflicts_with_all = ["compare", "reverse", "verbose", "batch", "diagnose_local"])]
+        edns_probe: bool,
+        /// Query with an EDNS Client Subnet option for each subnet (e.g., 203.0.113.0/24)
+        #[arg(long, value_delimiter = ',', conflicts_with_all = ["compare", "reverse", "verbose", "batch", "diagnose_local", "edns_probe"])]
+        ecs: Vec<String>,
+        /// File listing client subnets to sweep, one "subnet [label]" per line
+        #[arg(long, conflicts_with_all = ["compare", "reverse", "verbose", "batch", "diagnose_local", "edns_probe"])]
+        ecs_file: Option<String>,
+        /// Re-query every N seconds, showing TTLs and timestamping answer changes
+        #[arg(long, value_name = "SECONDS", conflicts_with_all = ["compare", "reverse", "verbose", "batch", "diagnose_local", "edns_probe", "ecs", "ecs_file"])]
+        watch: Option<u64>,
+        /// Stop watching once the answer is exactly this (repeat for several records, or give an rcode like NXDOMAIN)
+        #[arg(long, requires = "watch")]
+        expect: Vec<String>,
+        /// Check an IP address (or, for RHSBLs, a domain) against DNS blocklists
+        #[arg(long, value_name = "TARGET", conflicts_with_all = ["domain", "compare", "reverse", "verbose", "batch", "diagnose_local", "edns_probe", "ecs", "ecs_file", "watch"])]
+        dnsbl: Option<String>,
+        /// Blocklist zones to query instead of the defaults (e.g., zen.spamhaus.org,bl.spamcop.net)
+        #[arg(long, value_delimiter = ',', requires = "dnsbl")]
+        dnsbl_zones: Vec<String>,
+        /// File listing blocklist zones, one per line
+        #[arg(long, requires = "dnsbl")]
+        dnsbl_zones_file: Option<String>,
+        /// Batch output format (csv or ndjson)
+        #[arg(long, default_value = "csv", requires = "batch")]
+        format: String,
+        /// Write batch results to a file instead of stdout
+        #[arg(short = 'o', long, requires = "batch")]
+        output: Option<String>,
+        /// Show the full response: header flags, all sections, TTLs and EDNS
+        #[arg(short = 'v', long, conflicts_with = "compare")]
+        verbose: bool,
+        /// Include a hex dump of the response wire format
+        #[arg(long, requires = "verbose")]
+        hex: bool,
+        /// Query timeout in seconds, for every lookup mode (one attempt, no retries)
+        #[arg(long, default_value = "5")]
+        timeout: u64,
     },
     /// Test HTTP/HTTPS connectivity
     Http {
@@ -69,9 +152,100 @@ pub enum Commands {
         /// Follow redirects
         #[arg(short = 'f', long)]
         follow_redirects: bool,
+        /// How many redirects to follow with --follow-redirects [default: 5]
+        #[arg(long, value_name = "N", requires = "follow_redirects")]
+        max_redirects: Option<usize>,
         /// Show response headers
-        #[arg(short = 'H', long)]
+        #[arg(short = 'i', long)]
         show_headers: bool,
+        /// Request method (defaults to GET, or POST with --data)
+        #[arg(short = 'X', long = "request", value_name = "METHOD")]
+        method: Option<String>,
+        /// Extra request header, may be repeated (e.g., -H 'Accept: application/json')
+        #[arg(short = 'H', long = "header", value_name = "NAME: VALUE")]
+        headers: Vec<String>,
+        /// Request body, or @file to read it from a file
+        #[arg(short = 'd', long)]
+        data: Option<String>,
+        /// Basic auth credentials as user:password
+        #[arg(short = 'u', long, conflicts_with = "bearer")]
+        user: Option<String>,
+        /// Bearer token for the Authorization header
+        #[arg(long, value_name = "TOKEN")]
+        bearer: Option<String>,
+        /// User-Agent to send [default: netdiag/0.1.0]
+        #[arg(short = 'A', long)]
+        user_agent: Option<String>,
+        /// Do not require a valid certificate for https:// URLs
+        #[arg(short = 'k', long)]
+        insecure: bool,
+        /// Show the TLS session and certificate chain of https:// URLs
+        #[arg(long)]
+        tls_info: bool,
+        /// Ask for a compressed response (gzip, deflate, br) and decode it
+        #[arg(long)]
+        compressed: bool,
+        /// Require HTTP/2 (ALPN h2; prior knowledge for http:// URLs)
+        #[arg(long, conflicts_with = "http1_1")]
+        http2: bool,
+        /// Only speak HTTP/1.1, even if the server offers HTTP/2
+        #[arg(long = "http1.1")]
+        http1_1: bool,
+        /// Use HTTP/3 over QUIC and report the QUIC handshake
+        #[arg(long, conflicts_with_all = ["http2", "http1_1"])]
+        http3: bool,
+        /// Save the decoded response body to a file
+        #[arg(short = 'o', long)]
+        output: Option<String>,
+        /// Proxy URL: http://[user:password@]host:port, socks5:// or socks5h://
+        #[arg(short = 'x', long, value_name = "URL")]
+        proxy: Option<String>,
+        /// Fail unless the status is one of these, e.g. 200,204 or 2xx
+        #[arg(long, value_name = "CODES")]
+        expect_status: Option<String>,
+        /// Fail unless the response has this header ('Name: value', or just 'Name'), may be repeated
+        #[arg(long = "expect-header", value_name = "HEADER")]
+        expect_headers: Vec<String>,
+        /// Fail unless the body matches this regular expression
+        #[arg(long, value_name = "REGEX")]
+        expect_body_regex: Option<String>,
+        /// Fail if the response takes longer, e.g. 500ms or 2s (redirects included)
+        #[arg(long, value_name = "DURATION")]
+        max_time: Option<String>,
+        /// Fail unless this JSONPath matches the JSON body ('$.path' or '$.path == value'), may be repeated
+        #[arg(long, value_name = "CHECK")]
+        expect_json: Vec<String>,
+    },
+    /// Inspect a TLS server: protocol, cipher, ALPN, certificate chain, OCSP stapling
+    Tls {
+        /// Target as host[:port] (port defaults to 443)
+        target: String,
+        /// Server name to send in SNI and check the certificate against (defaults to the host)
+        #[arg(long)]
+        sni: Option<String>,
+        /// Connection and handshake timeout in seconds
+        #[arg(short = 't', long, default_value = "10")]
+        timeout: u64,
+    },
+    /// Check a domain's mail setup: MX, SPF, DMARC, DKIM, MTA-STS and SMTP TLS
+    Mail {
+        /// Mail domain to check
+        domain: String,
+        /// DKIM selectors to check (e.g., google,selector1)
+        #[arg(short = 'd', long = "dkim-selector", value_delimiter = ',')]
+        dkim_selectors: Vec<String>,
+        /// DNS server to use (optional)
+        #[arg(short = 's', long)]
+        server: Option<String>,
+        /// Skip connecting to the MX hosts on port 25
+        #[arg(long)]
+        no_smtp: bool,
+        /// Name to announce in EHLO
+        #[arg(long, default_value = "localhost")]
+        helo: String,
+        /// Timeout per SMTP and HTTPS check in seconds
+        #[arg(short = 't', long, default_value = "10")]
+        timeout: u64,
     },
     /// Trace network path to destination
     Trace {
@@ -83,6 +257,9 @@ pub enum Commands {
         /// Timeout per hop in seconds
         #[arg(short = 't', long, default_value = "5")]
         timeout: u64,
+        /// Run against every resolved address, not just the preferred one
+        #[arg(short = 'a', long)]
+        all_addresses: bool,
     },
     /// Test connection to specific port
     Connect {
@@ -96,6 +273,24 @@ pub enum Commands {
         /// Test UDP instead of TCP
         #[arg(short = 'u', long)]
         udp: bool,
+        /// Run against every resolved address, instead of stopping at the first that connects
+        #[arg(short = 'a', long)]
+        all_addresses: bool,
+    },
+    /// Check IPv6 readiness: DNS64/NAT64, native vs synthesized AAAA, IPv6 reachability
+    #[command(name = "ipv6-check")]
+    Ipv6Check {
+        /// Target host or IP address
+        host: String,
+        /// TCP port to test reachability on
+        #[arg(short = 'p', long, default_value = "443")]
+        port: u16,
+        /// DNS server to use for DNS64 detection and lookups (optional)
+        #[arg(short = 's', long)]
+        server: Option<String>,
+        /// Connection timeout in seconds
+        #[arg(short = 't', long, default_value = "5")]
+        timeout: u64,
     },
     /// Generate network test report
     Report {
@@ -110,34 +305,258 @@ pub enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+pub enum DnsAction {
+    /// Benchmark resolver latency and reliability
+    Bench {
+        /// Resolvers to benchmark (e.g., 8.8.8.8,1.1.1.1:53)
+        #[arg(long = "resolver", value_delimiter = ',')]
+        resolvers: Vec<String>,
+        /// File listing resolvers to benchmark, one "address [label]" per line
+        #[arg(long)]
+        resolvers_file: Option<String>,
+        /// Domains to query (e.g., google.com,github.com)
+        #[arg(short = 'd', long = "domain", value_delimiter = ',')]
+        domains: Vec<String>,
+        /// File listing domains to query, one per line
+        #[arg(long)]
+        domains_file: Option<String>,
+        /// Number of repeated (cached) queries per domain
+        #[arg(short = 'n', long, default_value = "5")]
+        rounds: u32,
+        /// Record type to query
+        #[arg(short = 't', long, default_value = "A")]
+        record_type: String,
+        /// Query timeout in seconds
+        #[arg(long, default_value = "2")]
+        timeout: u64,
+    },
+    /// Attempt a zone transfer (AXFR/IXFR) over TCP
+    Axfr {
+        /// Zone to transfer
+        zone: String,
+        /// Authoritative server to transfer from (IP address or host name, optionally with :port); with --all-ns, the resolver (IP address) used to find the nameservers
+        #[arg(short = 's', long, required_unless_present = "all_ns")]
+        server: Option<String>,
+        /// Try every nameserver listed in the zone's NS records
+        #[arg(long)]
+        all_ns: bool,
+        /// Request an incremental transfer from this SOA serial
+        #[arg(long)]
+        ixfr: Option<u32>,
+        /// Write the transferred zone to a file in master-file format (not written for incremental IXFR answers)
+        #[arg(short = 'o', long)]
+        output: Option<String>,
+        /// Transfer timeout in seconds
+        #[arg(long, default_value = "30")]
+        timeout: u64,
+    },
+    /// Build a query by hand and show the raw response
+    Query {
+        /// Name to query (e.g., version.bind with --class CH)
+        name: String,
+        /// Server to send the query to (defaults to the first system nameserver)
+        #[arg(short = 's', long)]
+        server: Option<String>,
+        /// Record type: any mnemonic, a number or TYPEnnn
+        #[arg(short = 't', long, default_value = "A")]
+        record_type: String,
+        /// Query class (IN, CH, HS, NONE, ANY)
+        #[arg(long, default_value = "IN")]
+        class: String,
+        /// Opcode, by name or number 0-15
+        #[arg(long, default_value = "QUERY")]
+        opcode: String,
+        /// Clear the RD (recursion desired) bit
+        #[arg(long)]
+        no_rd: bool,
+        /// Set the CD (checking disabled) bit
+        #[arg(long)]
+        cd: bool,
+        /// Set the EDNS DO (DNSSEC OK) bit
+        #[arg(long = "do")]
+        dnssec_ok: bool,
+        /// Send an EDNS Client Subnet option (e.g., 203.0.113.0/24)
+        #[arg(long)]
+        ecs: Option<String>,
+        /// Ask the server for its NSID
+        #[arg(long)]
+        nsid: bool,
+        /// Send no OPT record at all
+        #[arg(long, conflicts_with_all = ["dnssec_ok", "ecs", "nsid", "payload"])]
+        no_edns: bool,
+        /// Advertised EDNS UDP payload size
+        #[arg(long, default_value = "1232")]
+        payload: u16,
+        /// Send the query over TCP
+        #[arg(long)]
+        tcp: bool,
+        /// Include hex dumps of the request and response
+        #[arg(long)]
+        hex: bool,
+        /// Query timeout in seconds
+        #[arg(long, default_value = "5")]
+        timeout: u64,
+    },
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+
+    set_address_family(if cli.ipv4 {
+        AddressFamily::V4
+    } else if cli.ipv6 {
+        AddressFamily::V6
+    } else {
+        AddressFamily::Any
+    });
     
-    println!("{}", format!("🔍 NetDiag - Network Diagnostic Tool v{}", env!("CARGO_PKG_VERSION")).bright_cyan().bold());
-    println!();
+    // Batch results on stdout are CSV/NDJSON for other tools, keep them clean
+    let machine_output = matches!(cli.command, Commands::Dns { action: None, batch: Some(_), output: None, .. });
+    if !machine_output {
+        println!("{}", format!("🔍 NetDiag - Network Diagnostic Tool v{}", env!("CARGO_PKG_VERSION")).bright_cyan().bold());
+        println!();
+    }
 
-    match cli.command {
-        Commands::Ping { host, count, timeout, size } => {
-            ping_command(host, count, Duration::from_secs(timeout), size).await
+    let result = match cli.command {
+        Commands::Ping { host, count, timeout, size, all_addresses } => {
+            ping_command(host, count, Duration::from_secs(timeout), size, all_addresses).await
         }
-        Commands::Scan { host, ports, timeout, concurrency } => {
-            scan_command(host, ports, Duration::from_millis(timeout), concurrency).await
+        Commands::Scan { host, ports, timeout, concurrency, all_addresses } => {
+            scan_command(host, ports, Duration::from_millis(timeout), concurrency, all_addresses).await
         }
-        Commands::Dns { domain, server, record_type } => {
-            dns_command(domain, server, record_type).await
+        Commands::Dns { action: Some(action), .. } => match action {
+            DnsAction::Bench { resolvers, resolvers_file, domains, domains_file, rounds, record_type, timeout } => {
+                dns_bench_command(resolvers, resolvers_file, domains, domains_file, rounds, record_type, Duration::from_secs(timeout)).await
+            }
+            DnsAction::Axfr { zone, server, all_ns, ixfr, output, timeout } => {
+                dns_axfr_command(zone, server, all_ns, ixfr, output, Duration::from_secs(timeout)).await
+            }
+            DnsAction::Query { name, server, record_type, class, opcode, no_rd, cd, dnssec_ok, ecs, nsid, no_edns, payload, tcp, hex, timeout } => {
+                let options = QueryOptions {
+                    record_type,
+                    class,
+                    opcode,
+                    recursion_desired: !no_rd,
+                    checking_disabled: cd,
+                    dnssec_ok,
+                    client_subnet: ecs,
+                    nsid,
+                    payload: if no_edns { None } else { Some(payload) },
+                    tcp,
+                    show_hex: hex,
+                };
+                dns_query_command(name, server, options, Duration::from_secs(timeout)).await
+            }
+        },
+        Commands::Dns { action: None, domain, server, record_type, compare, resolvers, resolvers_file, authoritative, reverse, concurrency, batch, diagnose_local, edns_probe, ecs, ecs_file, watch, expect, dnsbl, dnsbl_zones, dnsbl_zones_file, format, output, verbose, hex, timeout } => {
+            if diagnose_local {
+                return dns_diagnose_local_command(domain, record_type, Duration::from_secs(timeout)).await;
+            }
+            let domain = domain.unwrap_or_default();
+            if let Some(target) = dnsbl {
+                return dns_dnsbl_command(target, server, dnsbl_zones, dnsbl_zones_file, Duration::from_secs(timeout)).await;
+            }
+            if let Some(path) = batch {
+                dns_batch_command(path, server, record_type, concurrency, format, output, Duration::from_secs(timeout)).await
+            } else if compare {
+                dns_compare_command(domain, server, record_type, resolvers, resolvers_file, authoritative, Duration::from_secs(timeout)).await
+            } else if let Some(interval) = watch {
+                dns_watch_command(domain, server, record_type, interval, expect, Duration::from_secs(timeout)).await
+            } else if !ecs.is_empty() || ecs_file.is_some() {
+                dns_ecs_command(domain, server, record_type, ecs, ecs_file, concurrency, Duration::from_secs(timeout)).await
+            } else if edns_probe {
+                dns_edns_probe_command(domain, server, record_type, Duration::from_secs(timeout)).await
+            } else if verbose {
+                dns_verbose_command(domain, server, record_type, hex, Duration::from_secs(timeout)).await
+            } else if reverse || is_reverse_target(&domain) {
+                dns_reverse_command(domain, server, concurrency, Duration::from_secs(timeout)).await
+            } else {
+                dns_command(domain, server, record_type, Duration::from_secs(timeout)).await
+            }
         }
-        Commands::Http { url, timeout, follow_redirects, show_headers } => {
-            http_command(url, Duration::from_secs(timeout), follow_redirects, show_headers).await
+        Commands::Http {
+            url,
+            timeout,
+            follow_redirects,
+            max_redirects,
+            show_headers,
+            method,
+            headers,
+            data,
+            user,
+            bearer,
+            user_agent,
+            insecure,
+            tls_info,
+            compressed,
+            http2,
+            http1_1,
+            http3,
+            output,
+            proxy,
+            expect_status,
+            expect_headers,
+            expect_body_regex,
+            max_time,
+            expect_json,
+        } => {
+            let version = if http3 {
+                HttpVersion::Http3
+            } else if http2 {
+                HttpVersion::Http2
+            } else if http1_1 {
+                HttpVersion::Http1
+            } else {
+                HttpVersion::Auto
+            };
+            let options = HttpOptions {
+                version,
+                follow_redirects,
+                max_redirects,
+                show_headers,
+                insecure,
+                tls_info,
+                compressed,
+                output,
+                method,
+                headers,
+                data,
+                user,
+                bearer,
+                user_agent,
+                proxy,
+                expect_status,
+                expect_headers,
+                expect_body_regex,
+                max_time,
+                expect_json,
+            };
+            http_command(url, options, Duration::from_secs(timeout)).await
         }
-        Commands::Trace { host, max_hops, timeout } => {
-            trace_command(host, max_hops, Duration::from_secs(timeout)).await
+        Commands::Tls { target, sni, timeout } => {
+            tls_command(target, sni, Duration::from_secs(timeout)).await
         }
-        Commands::Connect { host, port, timeout, udp } => {
-            connect_command(host, port, Duration::from_secs(timeout), udp).await
+        Commands::Mail { domain, dkim_selectors, server, no_smtp, helo, timeout } => {
+            mail_command(domain, server, dkim_selectors, no_smtp, helo, Duration::from_secs(timeout)).await
+        }
+        Commands::Trace { host, max_hops, timeout, all_addresses } => {
+            trace_command(host, max_hops, Duration::from_secs(timeout), all_addresses).await
+        }
+        Commands::Connect { host, port, timeout, udp, all_addresses } => {
+            connect_command(host, port, Duration::from_secs(timeout), udp, all_addresses).await
+        }
+        Commands::Ipv6Check { host, port, server, timeout } => {
+            ipv6_check_command(host, port, server, Duration::from_secs(timeout)).await
         }
         Commands::Report { host, output, detailed_scan } => {
             report_command(host, output, detailed_scan).await
         }
+    };
+
+    if let Some(ExitStatus(code)) = result.as_ref().err().and_then(|e| e.downcast_ref::<ExitStatus>()) {
+        std::process::exit(*code);
     }
+    result
 }
\ No newline at end of file
@@ -0,0 +1,575 @@
+use anyhow::Result;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::time;
+
+use trust_dns_resolver::proto::op::{Edns, Message, MessageType, OpCode, Query, ResponseCode};
+use trust_dns_resolver::proto::rr::rdata::opt::EdnsOption;
+use trust_dns_resolver::proto::rr::rdata::SOA;
+use trust_dns_resolver::proto::rr::{DNSClass, Name, RData, Record, RecordType};
+use trust_dns_resolver::system_conf::read_system_conf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    Udp,
+    Tcp,
+}
+
+impl std::fmt::Display for Transport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Transport::Udp => write!(f, "UDP"),
+            Transport::Tcp => write!(f, "TCP"),
+        }
+    }
+}
+
+/// A single DNS question sent straight to one server, bypassing the
+/// resolver cache so that the raw response can be inspected.
+#[derive(Debug, Clone)]
+pub struct DnsQuery {
+    name: Name,
+    record_type: RecordType,
+    query_class: DNSClass,
+    /// Raw 4-bit opcode, so values trust-dns has no name for can be sent too
+    op_code: u8,
+    recursion_desired: bool,
+    checking_disabled: bool,
+    edns_payload: Option<u16>,
+    dnssec_ok: bool,
+    edns_options: Vec<(u16, Vec<u8>)>,
+}
+
+impl DnsQuery {
+    pub fn new(name: &str, record_type: RecordType) -> Result<Self> {
+        let name = Name::from_ascii(name)
+            .map_err(|e| anyhow::anyhow!("Invalid domain name {}: {}", name, e))?;
+
+        Ok(Self {
+            name,
+            record_type,
+            query_class: DNSClass::IN,
+            op_code: 0,
+            recursion_desired: true,
+            checking_disabled: false,
+            edns_payload: Some(1232),
+            dnssec_ok: false,
+            edns_options: Vec::new(),
+        })
+    }
+
+    pub fn recursion_desired(mut self, recursion_desired: bool) -> Self {
+        self.recursion_desired = recursion_desired;
+        self
+    }
+
+    pub fn query_class(mut self, query_class: DNSClass) -> Self {
+        self.query_class = query_class;
+        self
+    }
+
+    pub fn op_code(mut self, op_code: u8) -> Self {
+        self.op_code = op_code & 0x0f;
+        self
+    }
+
+    pub fn checking_disabled(mut self, checking_disabled: bool) -> Self {
+        self.checking_disabled = checking_disabled;
+        self
+    }
+
+    /// Sets the advertised EDNS UDP payload size, or sends no OPT record at all.
+    pub fn edns_payload(mut self, payload: Option<u16>) -> Self {
+        self.edns_payload = payload;
+        self
+    }
+
+    /// Sets the DO bit, asking for DNSSEC records (which makes answers larger).
+    pub fn dnssec_ok(mut self, dnssec_ok: bool) -> Self {
+        self.dnssec_ok = dnssec_ok;
+        self
+    }
+
+    /// Attaches a raw EDNS option (e.g. NSID = 3, COOKIE = 10) to the query.
+    pub fn edns_option(mut self, code: u16, data: Vec<u8>) -> Self {
+        self.edns_options.push((code, data));
+        self
+    }
+
+    /// Encodes the query, patching in the opcode afterwards since trust-dns
+    /// only knows the assigned ones.
+    pub fn to_wire(&self, id: u16) -> Result<Vec<u8>> {
+        let mut query = Query::query(self.name.clone(), self.record_type);
+        query.set_query_class(self.query_class);
+
+        let mut message = Message::new();
+        message
+            .set_id(id)
+            .set_message_type(MessageType::Query)
+            .set_op_code(OpCode::Query)
+            .set_recursion_desired(self.recursion_desired)
+            .set_checking_disabled(self.checking_disabled)
+            .add_query(query);
+
+        if let Some(payload) = self.edns_payload {
+            let mut edns = Edns::new();
+            edns.set_max_payload(payload).set_version(0).set_dnssec_ok(self.dnssec_ok);
+            for (code, data) in &self.edns_options {
+                edns.options_mut().insert(EdnsOption::Unknown(*code, data.clone()));
+            }
+            message.set_edns(edns);
+        }
+
+        let mut wire = message
+            .to_vec()
+            .map_err(|e| anyhow::anyhow!("Failed to encode DNS query: {}", e))?;
+        wire[2] = (wire[2] & 0b1000_0111) | (self.op_code << 3);
+        Ok(wire)
+    }
+}
+
+pub struct DnsResponse {
+    pub message: Message,
+    /// The response exactly as received, without the TCP length prefix
+    pub wire: Vec<u8>,
+    pub elapsed: Duration,
+}
+
+/// Sends `query` to `server` over the given transport and waits for the
+/// matching response.
+pub async fn send_query(
+    server: SocketAddr,
+    query: &DnsQuery,
+    transport: Transport,
+    timeout: Duration,
+) -> Result<DnsResponse> {
+    let id: u16 = rand::random();
+    let request = query.to_wire(id)?;
+
+    let start_time = Instant::now();
+    let wire = match time::timeout(timeout, exchange(server, &request, id, transport)).await {
+        Ok(result) => result?,
+        Err(_) => return Err(anyhow::anyhow!("Query to {} timed out", server)),
+    };
+    let elapsed = start_time.elapsed();
+
+    let message = parse_message(&wire)
+        .map_err(|e| anyhow::anyhow!("Malformed DNS response from {}: {}", server, e))?;
+
+    Ok(DnsResponse {
+        message,
+        wire,
+        elapsed,
+    })
+}
+
+/// Parses a DNS message. Replies to unassigned opcodes are parsed as if they
+/// were QUERY; the real opcode stays readable in the wire bytes.
+fn parse_message(wire: &[u8]) -> Result<Message> {
+    match Message::from_vec(wire) {
+        Ok(message) => Ok(message),
+        Err(e) if wire.len() > 2 && OpCode::from_u8(wire_op_code(wire)).is_err() => {
+            let mut patched = wire.to_vec();
+            patched[2] &= 0b1000_0111;
+            Message::from_vec(&patched).map_err(|_| anyhow::anyhow!("{}", e))
+        }
+        Err(e) => Err(anyhow::anyhow!("{}", e)),
+    }
+}
+
+/// The 4-bit opcode of an encoded message.
+pub fn wire_op_code(wire: &[u8]) -> u8 {
+    wire.get(2).map(|byte| (byte >> 3) & 0x0f).unwrap_or(0)
+}
+
+pub fn op_code_name(op_code: u8) -> String {
+    match op_code {
+        0 => "QUERY".to_string(),
+        1 => "IQUERY".to_string(),
+        2 => "STATUS".to_string(),
+        4 => "NOTIFY".to_string(),
+        5 => "UPDATE".to_string(),
+        6 => "DSO".to_string(),
+        other => format!("OPCODE{}", other),
+    }
+}
+
+async fn exchange(server: SocketAddr, request: &[u8], id: u16, transport: Transport) -> Result<Vec<u8>> {
+    match transport {
+        Transport::Udp => {
+            let local_addr: SocketAddr = if server.is_ipv4() {
+                "0.0.0.0:0".parse().unwrap()
+            } else {
+                "[::]:0".parse().unwrap()
+            };
+            let socket = UdpSocket::bind(local_addr).await?;
+            socket.connect(server).await?;
+            socket.send(request).await?;
+
+            let mut buffer = vec![0u8; 65535];
+            loop {
+                let len = socket.recv(&mut buffer).await?;
+                // Ignore stray datagrams that don't answer our query
+                if len >= 2 && u16::from_be_bytes([buffer[0], buffer[1]]) == id {
+                    buffer.truncate(len);
+                    return Ok(buffer);
+                }
+            }
+        }
+        Transport::Tcp => {
+            let mut stream = TcpStream::connect(server).await?;
+            write_tcp_message(&mut stream, request).await?;
+            read_tcp_message(&mut stream).await
+        }
+    }
+}
+
+/// Writes one DNS message with the two-byte length prefix used over TCP.
+async fn write_tcp_message(stream: &mut TcpStream, message: &[u8]) -> Result<()> {
+    let mut framed = Vec::with_capacity(message.len() + 2);
+    framed.extend_from_slice(&(message.len() as u16).to_be_bytes());
+    framed.extend_from_slice(message);
+    stream.write_all(&framed).await?;
+    Ok(())
+}
+
+async fn read_tcp_message(stream: &mut TcpStream) -> Result<Vec<u8>> {
+    let mut length = [0u8; 2];
+    stream.read_exact(&mut length).await?;
+    let mut buffer = vec![0u8; u16::from_be_bytes(length) as usize];
+    stream.read_exact(&mut buffer).await?;
+    Ok(buffer)
+}
+
+pub struct ZoneTransfer {
+    pub rcode: ResponseCode,
+    /// All records in transfer order, including the leading and trailing SOA
+    pub records: Vec<Record>,
+    /// Whether the server finished the transfer rather than stopping early
+    pub complete: bool,
+    pub messages: usize,
+    pub bytes: usize,
+    pub elapsed: Duration,
+}
+
+/// Requests a full (AXFR) or, when `ixfr_serial` is given, incremental
+/// (IXFR) transfer of `zone` over TCP and collects every record sent.
+pub async fn zone_transfer(
+    server: SocketAddr,
+    zone: &str,
+    ixfr_serial: Option<u32>,
+    timeout: Duration,
+) -> Result<ZoneTransfer> {
+    let zone_name = Name::from_ascii(zone)
+        .map_err(|e| anyhow::anyhow!("Invalid zone name {}: {}", zone, e))?;
+
+    let id: u16 = rand::random();
+    let mut message = Message::new();
+    message
+        .set_id(id)
+        .set_message_type(MessageType::Query)
+        .set_op_code(OpCode::Query)
+        .set_recursion_desired(false);
+
+    match ixfr_serial {
+        Some(serial) => {
+            // IXFR carries the client's current SOA in the authority section
+            message.add_query(Query::query(zone_name.clone(), RecordType::IXFR));
+            let soa = SOA::new(Name::root(), Name::root(), serial, 0, 0, 0, 0);
+            message.add_name_server(Record::from_rdata(zone_name, 0, RData::SOA(soa)));
+        }
+        None => {
+            message.add_query(Query::query(zone_name, RecordType::AXFR));
+        }
+    }
+
+    let request = message
+        .to_vec()
+        .map_err(|e| anyhow::anyhow!("Failed to encode transfer request: {}", e))?;
+
+    let start_time = Instant::now();
+    let transfer = async {
+        let mut stream = TcpStream::connect(server).await?;
+        write_tcp_message(&mut stream, &request).await?;
+
+        let mut result = ZoneTransfer {
+            rcode: ResponseCode::NoError,
+            records: Vec::new(),
+            complete: false,
+            messages: 0,
+            bytes: 0,
+            elapsed: Duration::ZERO,
+        };
+
+        loop {
+            let wire = read_tcp_message(&mut stream).await?;
+            let response = Message::from_vec(&wire)
+                .map_err(|e| anyhow::anyhow!("Malformed transfer message from {}: {}", server, e))?;
+
+            result.messages += 1;
+            result.bytes += wire.len();
+            result.rcode = response.response_code();
+            result.records.extend(response.answers().iter().cloned());
+            result.complete = transfer_complete(&result.records, ixfr_serial.is_some());
+
+            if result.rcode != ResponseCode::NoError || response.answers().is_empty() || result.complete {
+                return Ok::<ZoneTransfer, anyhow::Error>(result);
+            }
+        }
+    };
+
+    let mut result = match time::timeout(timeout, transfer).await {
+        Ok(result) => result?,
+        Err(_) => return Err(anyhow::anyhow!("Zone transfer from {} timed out", server)),
+    };
+    result.elapsed = start_time.elapsed();
+
+    Ok(result)
+}
+
+/// Decides whether the records received so far form a complete transfer.
+///
+/// An AXFR ends with a repeat of the opening SOA. An IXFR answer is either a
+/// lone SOA (already up to date), an AXFR-style full zone, or a series of
+/// diffs in which the current SOA also opens the final batch of additions,
+/// so it appears three times in total.
+fn transfer_complete(records: &[Record], ixfr: bool) -> bool {
+    let serial_of = |record: &Record| match record.data() {
+        Some(RData::SOA(soa)) => Some(soa.serial()),
+        _ => None,
+    };
+
+    let current = match records.first().and_then(serial_of) {
+        Some(serial) => serial,
+        None => return false,
+    };
+
+    if ixfr && records.len() == 1 {
+        return true;
+    }
+
+    let last_is_current = records.len() > 1 && records.last().and_then(serial_of) == Some(current);
+    if !last_is_current {
+        return false;
+    }
+
+    let incremental = ixfr && records.get(1).and_then(serial_of).is_some();
+    let seen = records.iter().filter(|record| serial_of(record) == Some(current)).count();
+
+    seen >= if incremental { 3 } else { 2 }
+}
+
+/// Parses a DNS server given as `ip` or `ip:port`, defaulting to port 53.
+pub fn parse_server(server: &str) -> Result<SocketAddr> {
+    let server = server.trim();
+
+    if let Ok(addr) = server.parse::<SocketAddr>() {
+        return Ok(addr);
+    }
+
+    let ip = server
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+        .map_err(|_| anyhow::anyhow!("Invalid DNS server address: {}", server))?;
+
+    Ok(SocketAddr::new(ip, 53))
+}
+
+/// Returns the nameservers configured for this host, without duplicates.
+pub fn system_nameservers() -> Result<Vec<SocketAddr>> {
+    let (config, _) = read_system_conf()
+        .map_err(|e| anyhow::anyhow!("Failed to read system DNS configuration: {}", e))?;
+
+    let mut servers = Vec::new();
+    for name_server in config.name_servers() {
+        if !servers.contains(&name_server.socket_addr) {
+            servers.push(name_server.socket_addr);
+        }
+    }
+
+    Ok(servers)
+}
+
+/// Builds the `in-addr.arpa` / `ip6.arpa` name used for PTR lookups of `ip`.
+pub fn reverse_name(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(ipv4) => {
+            let octets = ipv4.octets();
+            format!("{}.{}.{}.{}.in-addr.arpa.", octets[3], octets[2], octets[1], octets[0])
+        }
+        IpAddr::V6(ipv6) => {
+            let mut name = String::with_capacity(73);
+            for byte in ipv6.octets().iter().rev() {
+                name.push_str(&format!("{:x}.{:x}.", byte & 0x0f, byte >> 4));
+            }
+            name.push_str("ip6.arpa.");
+            name
+        }
+    }
+}
+
+/// Returns the conventional mnemonic for a response code (NOERROR, NXDOMAIN, ...).
+pub fn rcode_name(rcode: ResponseCode) -> String {
+    match rcode {
+        ResponseCode::NoError => "NOERROR".to_string(),
+        ResponseCode::FormErr => "FORMERR".to_string(),
+        ResponseCode::ServFail => "SERVFAIL".to_string(),
+        ResponseCode::NXDomain => "NXDOMAIN".to_string(),
+        ResponseCode::NotImp => "NOTIMP".to_string(),
+        ResponseCode::Refused => "REFUSED".to_string(),
+        ResponseCode::NotAuth => "NOTAUTH".to_string(),
+        ResponseCode::BADVERS => "BADVERS".to_string(),
+        ResponseCode::BADCOOKIE => "BADCOOKIE".to_string(),
+        other => format!("RCODE{}", u16::from(other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_server_default_port() {
+        assert_eq!(parse_server("8.8.8.8").unwrap(), "8.8.8.8:53".parse().unwrap());
+        assert_eq!(parse_server("2001:db8::1").unwrap(), "[2001:db8::1]:53".parse().unwrap());
+        assert_eq!(parse_server("[2001:db8::1]").unwrap(), "[2001:db8::1]:53".parse().unwrap());
+    }
+
+    #[test]
+    fn test_parse_server_explicit_port() {
+        assert_eq!(parse_server("127.0.0.1:5353").unwrap(), "127.0.0.1:5353".parse().unwrap());
+        assert_eq!(parse_server("[::1]:5353").unwrap(), "[::1]:5353".parse().unwrap());
+    }
+
+    #[test]
+    fn test_parse_server_invalid() {
+        assert!(parse_server("dns.google").is_err());
+        assert!(parse_server("").is_err());
+    }
+
+    #[test]
+    fn test_reverse_name_ipv4() {
+        assert_eq!(reverse_name("192.0.2.10".parse().unwrap()), "10.2.0.192.in-addr.arpa.");
+    }
+
+    #[test]
+    fn test_reverse_name_ipv6() {
+        assert_eq!(
+            reverse_name("2001:db8::567:89ab".parse().unwrap()),
+            "b.a.9.8.7.6.5.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa."
+        );
+    }
+
+    fn soa_record(serial: u32) -> Record {
+        let soa = SOA::new(Name::root(), Name::root(), serial, 3600, 600, 86400, 300);
+        Record::from_rdata(Name::from_ascii("example.test.").unwrap(), 300, RData::SOA(soa))
+    }
+
+    fn a_record() -> Record {
+        Record::from_rdata(Name::from_ascii("www.example.test.").unwrap(), 300, RData::A("192.0.2.1".parse().unwrap()))
+    }
+
+    #[test]
+    fn test_transfer_complete_axfr() {
+        assert!(!transfer_complete(&[soa_record(5)], false));
+        assert!(!transfer_complete(&[soa_record(5), a_record()], false));
+        assert!(transfer_complete(&[soa_record(5), a_record(), soa_record(5)], false));
+    }
+
+    #[test]
+    fn test_transfer_complete_ixfr() {
+        // Already up to date
+        assert!(transfer_complete(&[soa_record(7)], true));
+
+        // One diff from 5 to 7: the current SOA shows up three times
+        let diff = vec![soa_record(7), soa_record(5), a_record(), soa_record(7), a_record()];
+        assert!(!transfer_complete(&diff, true));
+        let mut complete = diff.clone();
+        complete.push(soa_record(7));
+        assert!(transfer_complete(&complete, true));
+
+        // Server fell back to a full transfer
+        assert!(transfer_complete(&[soa_record(7), a_record(), soa_record(7)], true));
+    }
+
+    #[tokio::test]
+    async fn test_zone_transfer_multiple_messages() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let request = Message::from_vec(&read_tcp_message(&mut stream).await.unwrap()).unwrap();
+            assert_eq!(request.queries()[0].query_type(), RecordType::AXFR);
+
+            // Split the zone over two messages, as large transfers are
+            for answers in [vec![soa_record(42), a_record()], vec![a_record(), soa_record(42)]] {
+                let mut response = Message::new();
+                response
+                    .set_id(request.id())
+                    .set_message_type(MessageType::Response)
+                    .set_authoritative(true)
+                    .add_answers(answers);
+                write_tcp_message(&mut stream, &response.to_vec().unwrap()).await.unwrap();
+            }
+        });
+
+        let transfer = zone_transfer(server, "example.test.", None, Duration::from_secs(2)).await.unwrap();
+
+        assert_eq!(transfer.rcode, ResponseCode::NoError);
+        assert_eq!(transfer.messages, 2);
+        assert_eq!(transfer.records.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_send_query_udp() {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let server = socket.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let mut buffer = vec![0u8; 512];
+            let (len, peer) = socket.recv_from(&mut buffer).await.unwrap();
+            let request = Message::from_vec(&buffer[..len]).unwrap();
+
+            let mut response = Message::new();
+            response
+                .set_id(request.id())
+                .set_message_type(MessageType::Response)
+                .set_recursion_available(true)
+                .add_query(request.queries()[0].clone())
+                .add_answer(Record::from_rdata(
+                    request.queries()[0].name().clone(),
+                    300,
+                    RData::A("192.0.2.7".parse().unwrap()),
+                ));
+            socket.send_to(&response.to_vec().unwrap(), peer).await.unwrap();
+        });
+
+        let query = DnsQuery::new("example.test.", RecordType::A).unwrap();
+        let response = send_query(server, &query, Transport::Udp, Duration::from_secs(2)).await.unwrap();
+
+        assert_eq!(response.message.response_code(), ResponseCode::NoError);
+        assert_eq!(response.message.answers().len(), 1);
+        assert_eq!(response.message.answers()[0].ttl(), 300);
+    }
+
+    #[test]
+    fn test_unassigned_opcode_round_trip() {
+        let query = DnsQuery::new("hostname.bind.", RecordType::TXT)
+            .unwrap()
+            .query_class(DNSClass::CH)
+            .op_code(9);
+        let wire = query.to_wire(7).unwrap();
+        assert_eq!(wire_op_code(&wire), 9);
+
+        // trust-dns rejects opcode 9 itself, parse_message still reads the rest
+        assert!(Message::from_vec(&wire).is_err());
+        let message = parse_message(&wire).unwrap();
+        assert_eq!(message.id(), 7);
+        assert_eq!(message.queries()[0].query_class(), DNSClass::CH);
+        assert_eq!(op_code_name(wire_op_code(&wire)), "OPCODE9");
+    }
+}
@@ -0,0 +1,429 @@
+use anyhow::Result;
+use std::io::Read;
+use std::time::Instant;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt};
+
+/// Upper bound for the status line plus headers, and for a single chunk-size line.
+const MAX_HEAD_SIZE: usize = 64 * 1024;
+
+/// How the end of a response body is found (RFC 9112 section 6.3).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Framing {
+    /// HEAD responses and 1xx/204/304 never carry a body
+    Empty,
+    ContentLength(u64),
+    Chunked,
+    /// No length given: the body ends when the server closes the connection
+    UntilClose,
+    /// HTTP/2 and HTTP/3 carry the body in DATA frames on the stream
+    Frames,
+}
+
+impl std::fmt::Display for Framing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Framing::Empty => write!(f, "no body"),
+            Framing::ContentLength(_) => write!(f, "Content-Length"),
+            Framing::Chunked => write!(f, "chunked"),
+            Framing::UntilClose => write!(f, "until close"),
+            Framing::Frames => write!(f, "DATA frames"),
+        }
+    }
+}
+
+/// A parsed response. The body has its transfer framing removed but is
+/// still content-encoded; see [`decode_body`].
+pub struct Response {
+    pub version: String,
+    pub status_code: u16,
+    pub reason: String,
+    /// In the order received, names as sent
+    pub headers: Vec<(String, String)>,
+    pub framing: Framing,
+    pub body: Vec<u8>,
+    /// Body bytes as they crossed the wire, including chunk framing
+    pub wire_size: u64,
+    pub first_byte: Instant,
+}
+
+impl Response {
+    /// First value of the header `name`, compared case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+pub fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Reads one response from `stream`, skipping interim 1xx responses.
+/// `head_request` must be set for HEAD, whose response has no body even
+/// when it announces a length.
+pub async fn read_response<S>(stream: &mut S, head_request: bool) -> Result<Response>
+where
+    S: AsyncBufRead + Unpin,
+{
+    if stream.fill_buf().await?.is_empty() {
+        return Err(anyhow::anyhow!("Empty response"));
+    }
+    let first_byte = Instant::now();
+
+    loop {
+        let (version, status_code, reason) = parse_status_line(&read_line(stream).await?)?;
+        let headers = read_headers(stream).await?;
+
+        // 101 switches protocols, every other 1xx is followed by the real response
+        if (100..200).contains(&status_code) && status_code != 101 {
+            continue;
+        }
+
+        let framing = framing(status_code, &headers, head_request)?;
+        let (body, wire_size) = read_body(stream, framing).await?;
+
+        return Ok(Response {
+            version,
+            status_code,
+            reason,
+            headers,
+            framing,
+            body,
+            wire_size,
+            first_byte,
+        });
+    }
+}
+
+fn parse_status_line(line: &str) -> Result<(String, u16, String)> {
+    let mut parts = line.splitn(3, ' ');
+    let version = parts.next().unwrap_or_default();
+    if !version.starts_with("HTTP/") {
+        return Err(anyhow::anyhow!("Invalid status line: {}", line));
+    }
+
+    let status_code = parts
+        .next()
+        .and_then(|code| code.parse::<u16>().ok())
+        .filter(|code| (100..1000).contains(code))
+        .ok_or_else(|| anyhow::anyhow!("Invalid status code in: {}", line))?;
+    let reason = parts.next().unwrap_or_default().to_string();
+
+    Ok((version.to_string(), status_code, reason))
+}
+
+async fn read_headers<S>(stream: &mut S) -> Result<Vec<(String, String)>>
+where
+    S: AsyncBufRead + Unpin,
+{
+    let mut headers: Vec<(String, String)> = Vec::new();
+    let mut size = 0;
+
+    loop {
+        let line = read_line(stream).await?;
+        if line.is_empty() {
+            return Ok(headers);
+        }
+
+        size += line.len();
+        if size > MAX_HEAD_SIZE {
+            return Err(anyhow::anyhow!("Response headers exceed {} bytes", MAX_HEAD_SIZE));
+        }
+
+        // Obsolete line folding continues the previous value
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| anyhow::anyhow!("Malformed header line: {}", line))?;
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+}
+
+fn framing(status_code: u16, headers: &[(String, String)], head_request: bool) -> Result<Framing> {
+    if head_request || (100..200).contains(&status_code) || status_code == 204 || status_code == 304 {
+        return Ok(Framing::Empty);
+    }
+
+    // Transfer-Encoding wins over Content-Length; chunked must be the last coding
+    if let Some(encoding) = find_header(headers, "Transfer-Encoding") {
+        let last = encoding.rsplit(',').next().unwrap_or_default().trim();
+        return Ok(if last.eq_ignore_ascii_case("chunked") {
+            Framing::Chunked
+        } else {
+            Framing::UntilClose
+        });
+    }
+
+    match find_header(headers, "Content-Length") {
+        Some(length) => length
+            .trim()
+            .parse::<u64>()
+            .map(Framing::ContentLength)
+            .map_err(|_| anyhow::anyhow!("Invalid Content-Length: {}", length)),
+        None => Ok(Framing::UntilClose),
+    }
+}
+
+async fn read_body<S>(stream: &mut S, framing: Framing) -> Result<(Vec<u8>, u64)>
+where
+    S: AsyncBufRead + Unpin,
+{
+    match framing {
+        Framing::Empty => Ok((Vec::new(), 0)),
+        Framing::ContentLength(length) => {
+            let mut body = Vec::new();
+            let read = stream.take(length).read_to_end(&mut body).await?;
+            if (read as u64) < length {
+                return Err(anyhow::anyhow!("Connection closed after {} of {} body bytes", read, length));
+            }
+            Ok((body, length))
+        }
+        Framing::Chunked => read_chunked(stream).await,
+        Framing::Frames => Err(anyhow::anyhow!("HTTP/1 responses are not framed")),
+        Framing::UntilClose => {
+            let mut body = Vec::new();
+            match stream.read_to_end(&mut body).await {
+                Ok(_) => {}
+                // Many TLS servers close without a close_notify alert; what arrived is still the body
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {}
+                Err(e) => return Err(e.into()),
+            }
+            let size = body.len() as u64;
+            Ok((body, size))
+        }
+    }
+}
+
+async fn read_chunked<S>(stream: &mut S) -> Result<(Vec<u8>, u64)>
+where
+    S: AsyncBufRead + Unpin,
+{
+    let mut body = Vec::new();
+    let mut wire_size = 0u64;
+
+    loop {
+        let line = read_line(stream).await?;
+        wire_size += line.len() as u64 + 2;
+
+        // Chunk extensions after ';' carry nothing we need
+        let size_text = line.split(';').next().unwrap_or_default().trim();
+        let size = u64::from_str_radix(size_text, 16)
+            .map_err(|_| anyhow::anyhow!("Invalid chunk size: {}", line))?;
+
+        if size == 0 {
+            // Trailer fields up to the final empty line
+            loop {
+                let trailer = read_line(stream).await?;
+                wire_size += trailer.len() as u64 + 2;
+                if trailer.is_empty() {
+                    return Ok((body, wire_size));
+                }
+            }
+        }
+
+        let read = stream.take(size).read_to_end(&mut body).await?;
+        if (read as u64) < size {
+            return Err(anyhow::anyhow!("Connection closed inside a chunk"));
+        }
+        if !read_line(stream).await?.is_empty() {
+            return Err(anyhow::anyhow!("Missing CRLF after chunk data"));
+        }
+        wire_size += size + 2;
+    }
+}
+
+/// Reads a line ending in LF (CRLF or bare LF) without the terminator.
+async fn read_line<S>(stream: &mut S) -> Result<String>
+where
+    S: AsyncBufRead + Unpin,
+{
+    let mut line = Vec::new();
+    let read = stream.take(MAX_HEAD_SIZE as u64).read_until(b'\n', &mut line).await?;
+    if read == 0 {
+        return Err(anyhow::anyhow!("Connection closed in the middle of the response"));
+    }
+    if !line.ends_with(b"\n") {
+        return Err(anyhow::anyhow!("Line longer than {} bytes", MAX_HEAD_SIZE));
+    }
+
+    while line.last().is_some_and(|byte| *byte == b'\n' || *byte == b'\r') {
+        line.pop();
+    }
+    Ok(String::from_utf8_lossy(&line).to_string())
+}
+
+/// Undoes a Content-Encoding such as `gzip`, `deflate`, `br` or a list of
+/// them (applied in order, so removed in reverse).
+pub fn decode_body(body: &[u8], content_encoding: &str) -> Result<Vec<u8>> {
+    let mut decoded = body.to_vec();
+
+    for coding in content_encoding.rsplit(',').map(|coding| coding.trim().to_ascii_lowercase()) {
+        let mut output = Vec::new();
+        match coding.as_str() {
+            "" | "identity" => continue,
+            "gzip" | "x-gzip" => {
+                flate2::read::MultiGzDecoder::new(decoded.as_slice()).read_to_end(&mut output)?;
+            }
+            // Meant to be zlib-wrapped, but some servers send raw deflate
+            "deflate" => {
+                if flate2::read::ZlibDecoder::new(decoded.as_slice()).read_to_end(&mut output).is_err() {
+                    output.clear();
+                    flate2::read::DeflateDecoder::new(decoded.as_slice()).read_to_end(&mut output)?;
+                }
+            }
+            "br" => {
+                brotli::Decompressor::new(decoded.as_slice(), 4096).read_to_end(&mut output)?;
+            }
+            other => return Err(anyhow::anyhow!("Unsupported content encoding: {}", other)),
+        }
+        decoded = output;
+    }
+
+    Ok(decoded)
+}
+
+/// One alternative from an Alt-Svc header (RFC 7838).
+#[derive(Debug, PartialEq)]
+pub struct AltService {
+    /// ALPN id such as "h3" or "h3-29"
+    pub protocol: String,
+    /// `host:port`, where an empty host means the same host
+    pub authority: String,
+    /// Seconds the advertisement may be cached; 24 hours when not given
+    pub max_age: u64,
+}
+
+impl AltService {
+    pub fn port(&self) -> Option<u16> {
+        self.authority.rsplit_once(':').and_then(|(_, port)| port.parse().ok())
+    }
+}
+
+/// Parses an Alt-Svc value like `h3=":443"; ma=86400, h3-29=":443"`.
+/// `clear` and malformed entries yield nothing.
+pub fn parse_alt_svc(value: &str) -> Vec<AltService> {
+    value
+        .split(',')
+        .filter_map(|entry| {
+            let mut parameters = entry.split(';').map(str::trim);
+            let (protocol, authority) = parameters.next()?.split_once('=')?;
+            let authority = authority.trim().strip_prefix('"')?.strip_suffix('"')?;
+            let max_age = parameters
+                .filter_map(|parameter| parameter.split_once('='))
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case("ma"))
+                .and_then(|(_, value)| value.trim().trim_matches('"').parse().ok())
+                .unwrap_or(86400);
+
+            Some(AltService {
+                protocol: protocol.trim().to_string(),
+                authority: authority.to_string(),
+                max_age,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    async fn parse(raw: &[u8], head_request: bool) -> Result<Response> {
+        let mut stream = tokio::io::BufReader::new(raw);
+        read_response(&mut stream, head_request).await
+    }
+
+    #[tokio::test]
+    async fn test_content_length_framing_ignores_trailing_data() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 5\r\n\r\nhelloHTTP/1.1 200 OK\r\n";
+        let response = parse(raw, false).await.unwrap();
+
+        assert_eq!(response.version, "HTTP/1.1");
+        assert_eq!(response.reason, "OK");
+        assert_eq!(response.framing, Framing::ContentLength(5));
+        assert_eq!(response.body, b"hello");
+        assert_eq!(response.header("content-type"), Some("text/plain"));
+    }
+
+    #[tokio::test]
+    async fn test_chunked_framing() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5;ext=1\r\nhello\r\n7\r\n, world\r\n0\r\nX-Trailer: 1\r\n\r\n";
+        let response = parse(raw, false).await.unwrap();
+
+        assert_eq!(response.framing, Framing::Chunked);
+        assert_eq!(response.body, b"hello, world");
+        assert_eq!(response.wire_size, 9 + 7 + 3 + 9 + 3 + 14 + 2);
+    }
+
+    #[tokio::test]
+    async fn test_interim_and_bodiless_responses() {
+        let raw = b"HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 204 No Content\r\nContent-Length: 10\r\n\r\n";
+        let response = parse(raw, false).await.unwrap();
+        assert_eq!(response.status_code, 204);
+        assert_eq!(response.framing, Framing::Empty);
+
+        let head = parse(b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\n", true).await.unwrap();
+        assert!(head.body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_binary_body_until_close() {
+        let mut raw = b"HTTP/1.0 200 OK\r\n\r\n".to_vec();
+        raw.extend_from_slice(&[0xff, 0x00, 0xfe]);
+        let response = parse(&raw, false).await.unwrap();
+
+        assert_eq!(response.framing, Framing::UntilClose);
+        assert_eq!(response.body, vec![0xff, 0x00, 0xfe]);
+    }
+
+    #[tokio::test]
+    async fn test_truncated_and_malformed_responses() {
+        assert!(parse(b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nshort", false).await.is_err());
+        assert!(parse(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\n", false).await.is_err());
+        assert!(parse(b"SSH-2.0-OpenSSH_9.6\r\n", false).await.is_err());
+        assert!(parse(b"", false).await.is_err());
+    }
+
+    #[test]
+    fn test_decode_body() {
+        let text = b"netdiag ".repeat(50);
+
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(&text).unwrap();
+        let gzip = gzip.finish().unwrap();
+        assert_eq!(decode_body(&gzip, "gzip").unwrap(), text);
+
+        let mut deflate = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        deflate.write_all(&text).unwrap();
+        assert_eq!(decode_body(&deflate.finish().unwrap(), "deflate").unwrap(), text);
+
+        let mut br = Vec::new();
+        brotli::CompressorWriter::new(&mut br, 4096, 5, 22).write_all(&text).unwrap();
+        assert_eq!(decode_body(&br, "br").unwrap(), text);
+
+        assert_eq!(decode_body(b"plain", "identity").unwrap(), b"plain");
+        assert!(decode_body(b"data", "zstd").is_err());
+    }
+
+    #[test]
+    fn test_parse_alt_svc() {
+        let services = parse_alt_svc(r#"h3=":443"; ma=86400, h3-29="alt.example.com:8443"; persist=1, h2=":443""#);
+
+        assert_eq!(services.len(), 3);
+        assert_eq!(services[0].protocol, "h3");
+        assert_eq!(services[0].port(), Some(443));
+        assert_eq!(services[1].authority, "alt.example.com:8443");
+        assert_eq!(services[1].max_age, 86400);
+        assert_eq!(services[1].port(), Some(8443));
+        assert!(parse_alt_svc("clear").is_empty());
+    }
+}
@@ -0,0 +1,265 @@
+use anyhow::Result;
+use bytes::Bytes;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+const FRAME_HEADER_LEN: usize = 9;
+const FRAME_SETTINGS: u8 = 0x4;
+const FLAG_ACK: u8 = 0x1;
+
+/// One request/response exchange on a fresh HTTP/2 connection.
+pub struct Http2Response {
+    pub status_code: u16,
+    /// Lower-case names, in the order received
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub stream_id: u32,
+    /// The server's first SETTINGS frame as (identifier, value) pairs
+    pub settings: Vec<(u16, u32)>,
+    pub first_byte: Instant,
+}
+
+/// Sends `request` as stream 1 of a new HTTP/2 connection over `io`, which
+/// must already speak HTTP/2 (ALPN "h2", or cleartext prior knowledge).
+pub async fn send_request<S>(io: S, request: http::Request<()>, body: Option<Vec<u8>>) -> Result<Http2Response>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let settings = Arc::new(Mutex::new(SettingsSniffer::default()));
+    let tap = FrameTap {
+        inner: io,
+        sniffer: settings.clone(),
+    };
+
+    let (client, connection) = h2::client::handshake(tap).await.map_err(describe_error)?;
+    let driver = tokio::spawn(connection);
+
+    let result = async {
+        let mut client = client.ready().await.map_err(describe_error)?;
+        let (response, mut send_stream) = client.send_request(request, body.is_none()).map_err(describe_error)?;
+        if let Some(body) = body {
+            send_stream.send_data(Bytes::from(body), true).map_err(describe_error)?;
+        }
+
+        let response = response.await.map_err(describe_error)?;
+        let first_byte = Instant::now();
+
+        let (parts, mut stream) = response.into_parts();
+        let mut body = Vec::new();
+        while let Some(chunk) = stream.data().await {
+            let chunk = chunk.map_err(describe_error)?;
+            let _ = stream.flow_control().release_capacity(chunk.len());
+            body.extend_from_slice(&chunk);
+        }
+
+        let headers = parts
+            .headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).to_string()))
+            .collect();
+
+        Ok(Http2Response {
+            status_code: parts.status.as_u16(),
+            headers,
+            body,
+            stream_id: stream.stream_id().into(),
+            settings: Vec::new(),
+            first_byte,
+        })
+    }
+    .await;
+
+    driver.abort();
+    result.map(|mut response: Http2Response| {
+        response.settings = settings.lock().map(|sniffer| sniffer.settings.clone()).unwrap_or_default();
+        response
+    })
+}
+
+/// Turns stream resets and GOAWAYs into readable errors.
+fn describe_error(error: h2::Error) -> anyhow::Error {
+    match error.reason() {
+        Some(reason) if error.is_reset() => anyhow::anyhow!("HTTP/2 stream reset by the server: {:?} ({})", reason, reason),
+        Some(reason) if error.is_go_away() => anyhow::anyhow!("HTTP/2 connection closed by the server (GOAWAY {:?}: {})", reason, reason),
+        _ => anyhow::anyhow!("HTTP/2 error: {}", error),
+    }
+}
+
+/// RFC 9113 section 6.5.2 names, plus the common extensions.
+pub fn setting_name(identifier: u16) -> String {
+    match identifier {
+        0x1 => "HEADER_TABLE_SIZE".to_string(),
+        0x2 => "ENABLE_PUSH".to_string(),
+        0x3 => "MAX_CONCURRENT_STREAMS".to_string(),
+        0x4 => "INITIAL_WINDOW_SIZE".to_string(),
+        0x5 => "MAX_FRAME_SIZE".to_string(),
+        0x6 => "MAX_HEADER_LIST_SIZE".to_string(),
+        0x8 => "ENABLE_CONNECT_PROTOCOL".to_string(),
+        0x9 => "NO_RFC7540_PRIORITIES".to_string(),
+        other => format!("UNKNOWN_0x{:x}", other),
+    }
+}
+
+/// Passes everything through while the sniffer reads the server's frames,
+/// since h2 does not expose the settings the peer sent.
+struct FrameTap<S> {
+    inner: S,
+    sniffer: Arc<Mutex<SettingsSniffer>>,
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for FrameTap<S> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        let before = buf.filled().len();
+        let result = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = result {
+            if let Ok(mut sniffer) = self.sniffer.lock() {
+                sniffer.feed(&buf.filled()[before..]);
+            }
+        }
+        result
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for FrameTap<S> {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// Splits the server's byte stream into frames and keeps the payload of
+/// the first SETTINGS frame that is not an acknowledgement.
+#[derive(Default)]
+struct SettingsSniffer {
+    pending: Vec<u8>,
+    /// Length of the SETTINGS payload being collected
+    settings_len: Option<usize>,
+    /// Payload bytes of an uninteresting frame still to pass over
+    skip: usize,
+    done: bool,
+    settings: Vec<(u16, u32)>,
+}
+
+impl SettingsSniffer {
+    fn feed(&mut self, mut data: &[u8]) {
+        while !data.is_empty() && !self.done {
+            if self.skip > 0 {
+                let n = self.skip.min(data.len());
+                self.skip -= n;
+                data = &data[n..];
+                continue;
+            }
+
+            let need = self.settings_len.unwrap_or(FRAME_HEADER_LEN);
+            let n = (need - self.pending.len()).min(data.len());
+            self.pending.extend_from_slice(&data[..n]);
+            data = &data[n..];
+            if self.pending.len() < need {
+                return;
+            }
+
+            match self.settings_len.take() {
+                None => {
+                    let length = u32::from_be_bytes([0, self.pending[0], self.pending[1], self.pending[2]]) as usize;
+                    let (kind, flags) = (self.pending[3], self.pending[4]);
+                    self.pending.clear();
+
+                    if kind == FRAME_SETTINGS && flags & FLAG_ACK == 0 {
+                        if length == 0 {
+                            self.done = true;
+                        } else {
+                            self.settings_len = Some(length);
+                        }
+                    } else {
+                        self.skip = length;
+                    }
+                }
+                Some(_) => {
+                    self.settings = self
+                        .pending
+                        .chunks_exact(6)
+                        .map(|entry| {
+                            (
+                                u16::from_be_bytes([entry[0], entry[1]]),
+                                u32::from_be_bytes([entry[2], entry[3], entry[4], entry[5]]),
+                            )
+                        })
+                        .collect();
+                    self.pending.clear();
+                    self.done = true;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(kind: u8, flags: u8, payload: &[u8]) -> Vec<u8> {
+        let length = (payload.len() as u32).to_be_bytes();
+        let mut frame = vec![length[1], length[2], length[3], kind, flags, 0, 0, 0, 0];
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    #[test]
+    fn test_settings_sniffer_byte_by_byte() {
+        let mut stream = frame(0x8, 0, &[0, 0, 0, 1]);
+        stream.extend(frame(FRAME_SETTINGS, FLAG_ACK, &[]));
+        stream.extend(frame(FRAME_SETTINGS, 0, &[0, 3, 0, 0, 0, 100, 0, 4, 0, 1, 0, 0]));
+        stream.extend(frame(FRAME_SETTINGS, 0, &[0, 5, 0, 0, 0x40, 0]));
+
+        let mut sniffer = SettingsSniffer::default();
+        for byte in &stream {
+            sniffer.feed(std::slice::from_ref(byte));
+        }
+
+        assert_eq!(sniffer.settings, vec![(3, 100), (4, 65536)]);
+        assert_eq!(setting_name(3), "MAX_CONCURRENT_STREAMS");
+        assert_eq!(setting_name(0x42), "UNKNOWN_0x42");
+    }
+
+    #[tokio::test]
+    async fn test_cleartext_request() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut connection = h2::server::Builder::new()
+                .max_concurrent_streams(50)
+                .handshake::<_, Bytes>(socket)
+                .await
+                .unwrap();
+            while let Some(Ok((request, mut respond))) = connection.accept().await {
+                assert_eq!(request.uri().path(), "/status");
+                let response = http::Response::builder().status(204).header("x-backend", "b2").body(()).unwrap();
+                respond.send_response(response, true).unwrap();
+            }
+        });
+
+        let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let request = http::Request::builder()
+            .uri(format!("http://{}/status", addr))
+            .body(())
+            .unwrap();
+        let response = send_request(stream, request, None).await.unwrap();
+
+        assert_eq!(response.status_code, 204);
+        assert_eq!(response.stream_id, 1);
+        assert!(response.headers.contains(&("x-backend".to_string(), "b2".to_string())));
+        assert!(response.settings.contains(&(3, 50)));
+    }
+}
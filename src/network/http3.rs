@@ -0,0 +1,295 @@
+use anyhow::Result;
+use bytes::{Buf, Bytes};
+use quinn::crypto::rustls::{HandshakeData, QuicClientConfig};
+use rustls::pki_types::CertificateDer;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::tls::{client_config, summarize_certificate, RecordingVerifier, TlsInfo};
+
+/// QUIC version 1 (RFC 9000), the only version we offer.
+pub const QUIC_V1: u32 = 0x0000_0001;
+
+/// What happened when a second connection tried to resume the session with 0-RTT.
+#[derive(Debug, PartialEq)]
+pub enum ZeroRtt {
+    /// The server issued no ticket that allows early data
+    Unavailable,
+    Accepted,
+    Rejected,
+    /// The resumption attempt itself failed
+    Failed(String),
+}
+
+/// One request/response exchange on a fresh QUIC connection.
+pub struct Http3Response {
+    pub status_code: u16,
+    /// Lower-case names, in the order received
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub quic_version: u32,
+    /// From the first Initial packet to the handshake completing
+    pub handshake: Duration,
+    /// Smoothed round-trip time once the handshake is done
+    pub rtt: Duration,
+    pub zero_rtt: ZeroRtt,
+    pub tls: TlsInfo,
+    pub request_start: Instant,
+    pub first_byte: Instant,
+    /// End of the body, before the 0-RTT check starts
+    pub last_byte: Instant,
+}
+
+/// Connects to `addr` over QUIC (ALPN "h3", SNI `server_name`), sends
+/// `request` and reads the response, then opens a second connection to see
+/// whether the server accepts 0-RTT resumption. Each step gets `timeout`.
+///
+/// Certificates are checked like [`super::tls::handshake`] does.
+pub async fn send_request(
+    addr: SocketAddr,
+    server_name: &str,
+    require_valid: bool,
+    request: http::Request<()>,
+    body: Option<Vec<u8>>,
+    timeout: Duration,
+) -> Result<Http3Response> {
+    let (mut config, verifier) = client_config(&["h3"])?;
+    config.enable_early_data = true;
+    let config = QuicClientConfig::try_from(config).map_err(|e| anyhow::anyhow!("Failed to configure QUIC: {}", e))?;
+    let mut config = quinn::ClientConfig::new(Arc::new(config));
+    config.version(QUIC_V1);
+
+    let local: SocketAddr = if addr.is_ipv6() {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    };
+    let mut endpoint = quinn::Endpoint::client(local).map_err(|e| anyhow::anyhow!("Failed to open a UDP socket: {}", e))?;
+    endpoint.set_default_client_config(config);
+
+    let server_name = server_name.trim_end_matches('.');
+    let start = Instant::now();
+    let connecting = endpoint
+        .connect(addr, server_name)
+        .map_err(|e| anyhow::anyhow!("QUIC connect failed: {}", e))?;
+    let connection = tokio::time::timeout(timeout, connecting)
+        .await
+        .map_err(|_| anyhow::anyhow!("QUIC handshake timeout (is UDP port {} blocked?)", addr.port()))?
+        .map_err(describe_error)?;
+    let handshake = start.elapsed();
+
+    let tls = session_info(&connection, &verifier);
+    if require_valid {
+        if let Err(ref reason) = tls.verification {
+            connection.close(0u32.into(), b"");
+            return Err(anyhow::anyhow!("Certificate verification failed: {}", reason));
+        }
+    }
+
+    let request_start = Instant::now();
+    let response = tokio::time::timeout(timeout, exchange(connection.clone(), request, body))
+        .await
+        .map_err(|_| anyhow::anyhow!("Response timeout"));
+    let rtt = connection.rtt();
+    connection.close(0u32.into(), b"");
+    let (status_code, headers, body, first_byte) = response??;
+    let last_byte = Instant::now();
+
+    let zero_rtt = match tokio::time::timeout(timeout, resume(&endpoint, addr, server_name)).await {
+        Ok(outcome) => outcome,
+        Err(_) => ZeroRtt::Failed("timed out".to_string()),
+    };
+    endpoint.close(0u32.into(), b"");
+
+    Ok(Http3Response {
+        status_code,
+        headers,
+        body,
+        quic_version: QUIC_V1,
+        handshake,
+        rtt,
+        zero_rtt,
+        tls,
+        request_start,
+        first_byte,
+        last_byte,
+    })
+}
+
+type Exchanged = (u16, Vec<(String, String)>, Vec<u8>, Instant);
+
+async fn exchange(connection: quinn::Connection, request: http::Request<()>, body: Option<Vec<u8>>) -> Result<Exchanged> {
+    let (mut driver, mut sender) = h3::client::new(h3_quinn::Connection::new(connection))
+        .await
+        .map_err(|e| anyhow::anyhow!("HTTP/3 error: {}", e))?;
+    let driver = tokio::spawn(async move { driver.wait_idle().await });
+
+    let result = async {
+        let mut stream = sender.send_request(request).await?;
+        if let Some(body) = body {
+            stream.send_data(Bytes::from(body)).await?;
+        }
+        stream.finish().await?;
+
+        let response = stream.recv_response().await?;
+        let first_byte = Instant::now();
+
+        let mut body = Vec::new();
+        while let Some(mut chunk) = stream.recv_data().await? {
+            body.extend_from_slice(&chunk.copy_to_bytes(chunk.remaining()));
+        }
+
+        let headers = response
+            .headers()
+            .iter()
+            .map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).to_string()))
+            .collect();
+
+        Ok::<_, h3::error::StreamError>((response.status().as_u16(), headers, body, first_byte))
+    }
+    .await;
+
+    driver.abort();
+    result.map_err(|e| anyhow::anyhow!("HTTP/3 error: {}", e))
+}
+
+/// Opens a second connection with the session ticket from the first and
+/// reports whether the server took the 0-RTT attempt.
+async fn resume(endpoint: &quinn::Endpoint, addr: SocketAddr, server_name: &str) -> ZeroRtt {
+    let connecting = match endpoint.connect(addr, server_name) {
+        Ok(connecting) => connecting,
+        Err(e) => return ZeroRtt::Failed(e.to_string()),
+    };
+
+    match connecting.into_0rtt() {
+        Ok((connection, accepted)) => {
+            let outcome = if accepted.await { ZeroRtt::Accepted } else { ZeroRtt::Rejected };
+            connection.close(0u32.into(), b"");
+            outcome
+        }
+        Err(_) => ZeroRtt::Unavailable,
+    }
+}
+
+/// QUIC carries TLS 1.3 itself; quinn does not expose the cipher suite or group.
+fn session_info(connection: &quinn::Connection, verifier: &RecordingVerifier) -> TlsInfo {
+    let alpn = connection
+        .handshake_data()
+        .and_then(|data| data.downcast::<HandshakeData>().ok())
+        .and_then(|data| data.protocol)
+        .map(|protocol| String::from_utf8_lossy(&protocol).to_string());
+    let certificates = connection
+        .peer_identity()
+        .and_then(|identity| identity.downcast::<Vec<CertificateDer<'static>>>().ok())
+        .map(|chain| chain.iter().filter_map(|der| summarize_certificate(der).ok()).collect())
+        .unwrap_or_default();
+
+    TlsInfo {
+        protocol: "TLSv1.3".to_string(),
+        cipher: "unknown".to_string(),
+        key_exchange: None,
+        alpn,
+        ocsp_response_len: verifier.ocsp_response_len(),
+        certificates,
+        verification: verifier.outcome(),
+    }
+}
+
+fn describe_error(error: quinn::ConnectionError) -> anyhow::Error {
+    match error {
+        quinn::ConnectionError::VersionMismatch => anyhow::anyhow!("Server does not support QUIC version 1"),
+        quinn::ConnectionError::TimedOut => anyhow::anyhow!("QUIC handshake timed out"),
+        other => anyhow::anyhow!("QUIC handshake failed: {}", other),
+    }
+}
+
+/// e.g. "v1 (0x00000001)"
+pub fn version_name(version: u32) -> String {
+    match version {
+        QUIC_V1 => format!("v1 (0x{:08x})", version),
+        0x6b33_43cf => format!("v2 (0x{:08x})", version),
+        0xff00_0000..=0xff00_ffff => format!("draft-{} (0x{:08x})", version & 0xffff, version),
+        other => format!("0x{:08x}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::tls::self_signed_server_config;
+
+    /// Answers every request with 200 and an x-served-by header, over HTTP/3.
+    fn h3_server() -> SocketAddr {
+        let mut tls = self_signed_server_config(&["localhost"], &["h3"]);
+        tls.max_early_data_size = u32::MAX;
+        let config = quinn::crypto::rustls::QuicServerConfig::try_from(tls).unwrap();
+        let config = quinn::ServerConfig::with_crypto(Arc::new(config));
+        let endpoint = quinn::Endpoint::server(config, "127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = endpoint.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            while let Some(incoming) = endpoint.accept().await {
+                tokio::spawn(async move {
+                    let Ok(connection) = incoming.await else {
+                        return;
+                    };
+                    let mut connection = h3::server::Connection::<_, Bytes>::new(h3_quinn::Connection::new(connection))
+                        .await
+                        .unwrap();
+                    while let Ok(Some(resolver)) = connection.accept().await {
+                        let (request, mut stream) = resolver.resolve_request().await.unwrap();
+                        let response = http::Response::builder()
+                            .status(200)
+                            .header("x-served-by", "quic")
+                            .body(())
+                            .unwrap();
+                        stream.send_response(response).await.unwrap();
+                        stream.send_data(Bytes::from(request.uri().path().to_string())).await.unwrap();
+                        stream.finish().await.unwrap();
+                    }
+                });
+            }
+        });
+
+        addr
+    }
+
+    fn request(addr: SocketAddr) -> http::Request<()> {
+        http::Request::builder()
+            .uri(format!("https://localhost:{}/health", addr.port()))
+            .body(())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_request_over_quic() {
+        let addr = h3_server();
+        let response = send_request(addr, "localhost", false, request(addr), None, Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status_code, 200);
+        assert_eq!(response.body, b"/health");
+        assert!(response.headers.contains(&("x-served-by".to_string(), "quic".to_string())));
+        assert_eq!(response.quic_version, QUIC_V1);
+        assert_eq!(response.tls.alpn.as_deref(), Some("h3"));
+        assert!(response.tls.certificates[0].is_self_signed());
+        assert!(response.tls.verification.is_err());
+        assert_eq!(response.zero_rtt, ZeroRtt::Accepted);
+    }
+
+    #[tokio::test]
+    async fn test_untrusted_certificate_is_rejected() {
+        let addr = h3_server();
+        let result = send_request(addr, "localhost", true, request(addr), None, Duration::from_secs(5)).await;
+
+        assert!(result.err().unwrap().to_string().contains("Certificate verification failed"));
+    }
+
+    #[test]
+    fn test_version_name() {
+        assert_eq!(version_name(1), "v1 (0x00000001)");
+        assert_eq!(version_name(0xff00_001d), "draft-29 (0xff00001d)");
+    }
+}
@@ -1,3 +1,10 @@
+pub mod dns;
+pub mod http;
+pub mod http2;
+pub mod http3;
 pub mod icmp;
+pub mod proxy;
 pub mod resolver;
+pub mod smtp;
+pub mod tls;
 pub mod traceroute;
\ No newline at end of file
@@ -1,27 +1,260 @@
 use anyhow::Result;
-use std::net::IpAddr;
+use colored::*;
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::sync::OnceLock;
 
+use trust_dns_resolver::config::LookupIpStrategy;
+use trust_dns_resolver::system_conf::read_system_conf;
 use trust_dns_resolver::TokioAsyncResolver;
 
-pub async fn resolve_hostname(hostname: &str) -> Result<IpAddr> {
-    // Try to parse as IP first
+/// Which address families hostnames may resolve to (the global `-4`/`-6` flags).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AddressFamily {
+    #[default]
+    Any,
+    V4,
+    V6,
+}
+
+impl AddressFamily {
+    pub fn allows(self, ip: &IpAddr) -> bool {
+        match self {
+            AddressFamily::Any => true,
+            AddressFamily::V4 => ip.is_ipv4(),
+            AddressFamily::V6 => ip.is_ipv6(),
+        }
+    }
+}
+
+static FAMILY: OnceLock<AddressFamily> = OnceLock::new();
+static RESOLVER: OnceLock<TokioAsyncResolver> = OnceLock::new();
+
+/// Restricts every later lookup to one family. Only the first call has any effect.
+pub fn set_address_family(family: AddressFamily) {
+    let _ = FAMILY.set(family);
+}
+
+pub fn address_family() -> AddressFamily {
+    FAMILY.get().copied().unwrap_or_default()
+}
+
+/// The process-wide resolver. Sharing it means sharing its cache, so a name
+/// looked up by several tests of one command only goes to the network once.
+fn shared_resolver() -> Result<&'static TokioAsyncResolver> {
+    if let Some(resolver) = RESOLVER.get() {
+        return Ok(resolver);
+    }
+
+    let (config, mut options) =
+        read_system_conf().map_err(|e| anyhow::anyhow!("Failed to create DNS resolver: {}", e))?;
+    options.ip_strategy = match address_family() {
+        AddressFamily::Any => LookupIpStrategy::Ipv4AndIpv6,
+        AddressFamily::V4 => LookupIpStrategy::Ipv4Only,
+        AddressFamily::V6 => LookupIpStrategy::Ipv6Only,
+    };
+
+    Ok(RESOLVER.get_or_init(|| TokioAsyncResolver::tokio(config, options)))
+}
+
+/// Resolves `hostname` to all of its addresses, in the order connections
+/// should be attempted (see [`sort_addresses`]).
+pub async fn resolve_all(hostname: &str) -> Result<Vec<IpAddr>> {
+    let family = address_family();
+
     if let Ok(ip) = hostname.parse::<IpAddr>() {
-        return Ok(ip);
+        if !family.allows(&ip) {
+            return Err(anyhow::anyhow!("{} does not match the requested address family", ip));
+        }
+        return Ok(vec![ip]);
     }
 
-    // Create resolver
-    let resolver = TokioAsyncResolver::tokio_from_system_conf()
-        .map_err(|e| anyhow::anyhow!("Failed to create DNS resolver: {}", e))?;
+    let response = shared_resolver()?
+        .lookup_ip(hostname)
+        .await
+        .map_err(|e| anyhow::anyhow!("DNS lookup failed: {}", e))?;
 
-    // Try IPv4 first
-    match resolver.lookup_ip(hostname).await {
-        Ok(response) => {
-            if let Some(ip) = response.iter().next() {
-                Ok(ip)
-            } else {
-                Err(anyhow::anyhow!("No IP addresses found for hostname"))
+    let addresses: Vec<IpAddr> = response.iter().filter(|ip| family.allows(ip)).collect();
+    if addresses.is_empty() {
+        return Err(anyhow::anyhow!("No IP addresses found for hostname"));
+    }
+
+    Ok(sort_addresses(addresses, has_route))
+}
+
+/// Resolves a command's target host, printing the addresses when they say
+/// more than `host` itself, or why resolution failed. `None` means the
+/// command has nothing to work on.
+pub async fn resolve_target(host: &str) -> Option<Vec<IpAddr>> {
+    match resolve_all(host).await {
+        Ok(addresses) => {
+            if addresses.len() > 1 || addresses[0].to_string() != host {
+                let list: Vec<String> = addresses.iter().map(|ip| ip.to_string()).collect();
+                println!("Resolved {} to {}", host.bright_cyan(), list.join(", ").bright_yellow());
             }
+            Some(addresses)
+        }
+        Err(e) => {
+            println!("{} Failed to resolve hostname: {}", "❌".red(), e);
+            None
         }
-        Err(e) => Err(anyhow::anyhow!("DNS lookup failed: {}", e)),
     }
-}
\ No newline at end of file
+}
+
+/// Resolves `hostname` to its most preferred address.
+pub async fn resolve_hostname(hostname: &str) -> Result<IpAddr> {
+    resolve_all(hostname).await.map(|addresses| addresses[0])
+}
+
+/// Orders destinations following RFC 6724 and then interleaves the address
+/// families as Happy Eyeballs (RFC 8305 section 4) recommends, so a broken
+/// family only costs one attempt before the other is tried.
+///
+/// `reachable` stands in for the source address selection that RFC 6724
+/// relies on: addresses without a route sort last.
+pub fn sort_addresses(mut addresses: Vec<IpAddr>, reachable: impl Fn(&IpAddr) -> bool) -> Vec<IpAddr> {
+    // Keep the first of any repeated address, wherever the repeat appears
+    let mut seen = HashSet::new();
+    addresses.retain(|ip| seen.insert(*ip));
+
+    // Rule 1 (avoid unusable destinations), rule 6 (higher precedence),
+    // rule 8 (smaller scope); the sort is stable so DNS order breaks ties
+    addresses.sort_by_key(|ip| (!reachable(ip), std::cmp::Reverse(precedence(ip)), scope(ip)));
+
+    let first_is_v6 = addresses.first().is_some_and(|ip| ip.is_ipv6());
+    let (mut preferred, mut other): (Vec<IpAddr>, Vec<IpAddr>) =
+        addresses.into_iter().partition(|ip| ip.is_ipv6() == first_is_v6);
+    preferred.reverse();
+    other.reverse();
+
+    let mut ordered = Vec::with_capacity(preferred.len() + other.len());
+    while let Some(ip) = preferred.pop() {
+        ordered.push(ip);
+        ordered.extend(other.pop());
+    }
+    ordered.extend(other.into_iter().rev());
+    ordered
+}
+
+/// Precedence from the RFC 6724 default policy table.
+fn precedence(ip: &IpAddr) -> u8 {
+    let v6 = match ip {
+        IpAddr::V4(_) => return 35,
+        IpAddr::V6(v6) => v6,
+    };
+
+    let segments = v6.segments();
+    if *v6 == Ipv6Addr::LOCALHOST {
+        50
+    } else if v6.to_ipv4_mapped().is_some() {
+        35
+    } else if segments[0] == 0x2002 {
+        30
+    } else if segments[0] == 0x2001 && segments[1] == 0 {
+        5
+    } else if segments[0] & 0xfe00 == 0xfc00 {
+        3
+    } else if segments[..6].iter().all(|s| *s == 0) || segments[0] & 0xffc0 == 0xfec0 || segments[0] == 0x3ffe {
+        1
+    } else {
+        40
+    }
+}
+
+/// Address scope as used by RFC 6724 rule 8: link-local 2, site-local 5, global 14.
+fn scope(ip: &IpAddr) -> u8 {
+    match ip {
+        IpAddr::V4(v4) if v4.is_loopback() || v4.is_link_local() => 2,
+        IpAddr::V4(_) => 14,
+        IpAddr::V6(v6) if v6.is_loopback() || v6.segments()[0] & 0xffc0 == 0xfe80 => 2,
+        IpAddr::V6(v6) if v6.segments()[0] & 0xffc0 == 0xfec0 => 5,
+        IpAddr::V6(_) => 14,
+    }
+}
+
+/// Whether the host has a route (and so a source address) for `ip`.
+fn has_route(ip: &IpAddr) -> bool {
+    source_address(ip).is_some()
+}
+
+/// The local address the kernel would send from to reach `ip`, if there is
+/// a route at all. Connecting a UDP socket picks a route without sending anything.
+pub fn source_address(ip: &IpAddr) -> Option<IpAddr> {
+    let bind: SocketAddr = match ip {
+        IpAddr::V4(_) => "0.0.0.0:0".parse().unwrap(),
+        IpAddr::V6(_) => "[::]:0".parse().unwrap(),
+    };
+
+    let socket = UdpSocket::bind(bind).ok()?;
+    socket.connect(SocketAddr::new(*ip, 9)).ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ips(list: &[&str]) -> Vec<IpAddr> {
+        list.iter().map(|ip| ip.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_sort_interleaves_families() {
+        let addresses = ips(&["192.0.2.1", "192.0.2.2", "2001:db8::1", "2001:db8::2", "192.0.2.3"]);
+        let sorted = sort_addresses(addresses, |_| true);
+
+        // Global IPv6 outranks IPv4 in the policy table, then families alternate
+        assert_eq!(
+            sorted,
+            ips(&["2001:db8::1", "192.0.2.1", "2001:db8::2", "192.0.2.2", "192.0.2.3"])
+        );
+    }
+
+    #[test]
+    fn test_sort_removes_duplicates() {
+        let addresses = ips(&["192.0.2.1", "2001:db8::1", "192.0.2.1", "192.0.2.2", "2001:db8::1"]);
+        let sorted = sort_addresses(addresses, |_| true);
+
+        assert_eq!(sorted, ips(&["2001:db8::1", "192.0.2.1", "192.0.2.2"]));
+    }
+
+    #[test]
+    fn test_sort_unreachable_family_last() {
+        let addresses = ips(&["2001:db8::1", "192.0.2.1", "2001:db8::2"]);
+        let sorted = sort_addresses(addresses, |ip| ip.is_ipv4());
+
+        assert_eq!(sorted, ips(&["192.0.2.1", "2001:db8::1", "2001:db8::2"]));
+    }
+
+    #[test]
+    fn test_sort_precedence() {
+        let addresses = ips(&["2002:c000:0201::1", "fd00::1", "2001:db8::1"]);
+        let sorted = sort_addresses(addresses, |_| true);
+
+        assert_eq!(sorted, ips(&["2001:db8::1", "2002:c000:0201::1", "fd00::1"]));
+    }
+
+    #[test]
+    fn test_sort_6to4() {
+        let six_to_four: IpAddr = "2002:c000:0201::1".parse().unwrap();
+        assert_eq!(precedence(&six_to_four), 30);
+
+        // Native IPv6 (40) > IPv4 (35) > 6to4 (30) > Teredo (5)
+        let addresses = ips(&["2001:0:4136:e378::1", "2002:c000:0201::1", "192.0.2.1", "2001:db8::1"]);
+        let sorted = sort_addresses(addresses, |_| true);
+        assert_eq!(sorted, ips(&["2001:db8::1", "192.0.2.1", "2002:c000:0201::1", "2001:0:4136:e378::1"]));
+
+        let sorted = sort_addresses(ips(&["2002:c000:0201::1", "192.0.2.1"]), |_| true);
+        assert_eq!(sorted, ips(&["192.0.2.1", "2002:c000:0201::1"]));
+    }
+
+    #[test]
+    fn test_address_family_allows() {
+        let v4: IpAddr = "192.0.2.1".parse().unwrap();
+        let v6: IpAddr = "2001:db8::1".parse().unwrap();
+
+        assert!(AddressFamily::Any.allows(&v4) && AddressFamily::Any.allows(&v6));
+        assert!(AddressFamily::V4.allows(&v4) && !AddressFamily::V4.allows(&v6));
+        assert!(AddressFamily::V6.allows(&v6) && !AddressFamily::V6.allows(&v4));
+    }
+}
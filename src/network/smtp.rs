@@ -0,0 +1,243 @@
+use anyhow::Result;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+use super::tls::{handshake, TlsInfo};
+
+/// A complete (possibly multi-line) SMTP reply.
+pub struct SmtpReply {
+    pub code: u16,
+    pub lines: Vec<String>,
+}
+
+impl SmtpReply {
+    pub fn text(&self) -> String {
+        self.lines.join(" ")
+    }
+}
+
+pub struct SmtpProbe {
+    pub connect_time: Duration,
+    pub banner: SmtpReply,
+    /// EHLO keywords, e.g. "STARTTLS", "SIZE 35882577", "PIPELINING"
+    pub extensions: Vec<String>,
+    /// `None` when STARTTLS isn't advertised, otherwise the handshake outcome
+    pub starttls: Option<Result<TlsInfo, String>>,
+}
+
+impl SmtpProbe {
+    pub fn supports(&self, keyword: &str) -> bool {
+        self.extensions.iter().any(|extension| {
+            extension
+                .split_whitespace()
+                .next()
+                .is_some_and(|name| name.eq_ignore_ascii_case(keyword))
+        })
+    }
+}
+
+/// Connects to an SMTP server, reads the banner, runs EHLO and, when offered,
+/// upgrades with STARTTLS so the certificate can be inspected. `hostname` is
+/// used for SNI; `helo` is the name we announce ourselves as.
+pub async fn probe_smtp(addr: SocketAddr, hostname: &str, helo: &str, timeout: Duration) -> Result<SmtpProbe> {
+    tokio::time::timeout(timeout, run_probe(addr, hostname, helo))
+        .await
+        .map_err(|_| anyhow::anyhow!("Timed out after {}s", timeout.as_secs()))?
+}
+
+async fn run_probe(addr: SocketAddr, hostname: &str, helo: &str) -> Result<SmtpProbe> {
+    let start = Instant::now();
+    let stream = TcpStream::connect(addr).await?;
+    let connect_time = start.elapsed();
+
+    let mut stream = BufReader::new(stream);
+
+    let banner = read_reply(&mut stream).await?;
+    if banner.code != 220 {
+        return Err(anyhow::anyhow!("Server refused the session: {} {}", banner.code, banner.text()));
+    }
+
+    let ehlo = command(&mut stream, &format!("EHLO {}", helo)).await?;
+    if ehlo.code != 250 {
+        return Err(anyhow::anyhow!("EHLO rejected: {} {}", ehlo.code, ehlo.text()));
+    }
+    // The first line of the EHLO reply is the greeting, the rest are keywords
+    let extensions: Vec<String> = ehlo.lines.iter().skip(1).cloned().collect();
+
+    let mut probe = SmtpProbe {
+        connect_time,
+        banner,
+        extensions,
+        starttls: None,
+    };
+
+    if !probe.supports("STARTTLS") {
+        let _ = command(&mut stream, "QUIT").await;
+        return Ok(probe);
+    }
+
+    let reply = command(&mut stream, "STARTTLS").await?;
+    if reply.code != 220 {
+        probe.starttls = Some(Err(format!("STARTTLS rejected: {} {}", reply.code, reply.text())));
+        let _ = command(&mut stream, "QUIT").await;
+        return Ok(probe);
+    }
+
+    // Nothing may be buffered past the 220, so the raw stream is safe to hand over
+    probe.starttls = Some(match handshake(stream.into_inner(), hostname, false, &[]).await {
+        Ok((tls_stream, info)) => {
+            // Make sure the encrypted session actually works before quitting
+            let mut tls_stream = BufReader::new(tls_stream);
+            match command(&mut tls_stream, &format!("EHLO {}", helo)).await {
+                Ok(reply) if reply.code == 250 => {
+                    let _ = command(&mut tls_stream, "QUIT").await;
+                    Ok(info)
+                }
+                Ok(reply) => Err(format!("EHLO after STARTTLS rejected: {} {}", reply.code, reply.text())),
+                Err(e) => Err(format!("Session failed after STARTTLS: {}", e)),
+            }
+        }
+        Err(e) => Err(e.to_string()),
+    });
+
+    Ok(probe)
+}
+
+async fn command<S>(stream: &mut BufReader<S>, line: &str) -> Result<SmtpReply>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    stream.get_mut().write_all(format!("{}\r\n", line).as_bytes()).await?;
+    stream.get_mut().flush().await?;
+    read_reply(stream).await
+}
+
+/// Reads reply lines until one has a space after the code ("250 OK") rather
+/// than a hyphen ("250-SIZE"), which marks the last line.
+async fn read_reply<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<SmtpReply> {
+    let mut lines = Vec::new();
+
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Err(anyhow::anyhow!("Connection closed by server"));
+        }
+
+        let line = line.trim_end_matches(['\r', '\n']);
+        let code = line
+            .get(..3)
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or_else(|| anyhow::anyhow!("Malformed SMTP reply: {}", line))?;
+        let last = line.as_bytes().get(3) != Some(&b'-');
+        lines.push(line.get(4..).unwrap_or("").to_string());
+
+        if last {
+            return Ok(SmtpReply { code, lines });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::tls::self_signed_acceptor;
+    use tokio::net::TcpListener;
+
+    const GREETING: &str = "220 mx.test ESMTP stand-in\r\n";
+
+    async fn read_command<R: AsyncBufRead + Unpin>(reader: &mut R) -> String {
+        let mut line = String::new();
+        reader.read_line(&mut line).await.unwrap();
+        line.trim_end().to_string()
+    }
+
+    /// Serves EHLO/QUIT like a minimal MTA; returns the commands it received.
+    async fn serve_session<S: AsyncRead + AsyncWrite + Unpin>(stream: &mut BufReader<S>, ehlo: &str) -> Vec<String> {
+        let mut received = Vec::new();
+        loop {
+            let command = read_command(stream).await;
+            if command.is_empty() {
+                return received;
+            }
+            received.push(command.clone());
+
+            let reply = if command.starts_with("EHLO") {
+                ehlo.to_string()
+            } else if command == "STARTTLS" {
+                stream.get_mut().write_all(b"220 2.0.0 Ready to start TLS\r\n").await.unwrap();
+                return received;
+            } else {
+                "221 2.0.0 Bye\r\n".to_string()
+            };
+            stream.get_mut().write_all(reply.as_bytes()).await.unwrap();
+            if command == "QUIT" {
+                return received;
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_reply_multiline() {
+        let data: &[u8] = b"250-mx.test greets you\r\n250-SIZE 1000\r\n250 STARTTLS\r\n";
+        let reply = tokio_test::block_on(read_reply(&mut BufReader::new(data))).unwrap();
+
+        assert_eq!(reply.code, 250);
+        assert_eq!(reply.lines, vec!["mx.test greets you", "SIZE 1000", "STARTTLS"]);
+    }
+
+    #[tokio::test]
+    async fn test_probe_without_starttls() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut stream = BufReader::new(socket);
+            stream.get_mut().write_all(GREETING.as_bytes()).await.unwrap();
+            serve_session(&mut stream, "250-mx.test\r\n250-PIPELINING\r\n250 SIZE 1000\r\n").await
+        });
+
+        let probe = probe_smtp(addr, "mx.test", "client.test", Duration::from_secs(5)).await.unwrap();
+        let received = server.await.unwrap();
+
+        assert_eq!(probe.banner.code, 220);
+        assert_eq!(probe.banner.text(), "mx.test ESMTP stand-in");
+        assert!(probe.supports("size"));
+        assert!(!probe.supports("STARTTLS"));
+        assert!(probe.starttls.is_none());
+        assert_eq!(received, vec!["EHLO client.test", "QUIT"]);
+    }
+
+    #[tokio::test]
+    async fn test_probe_with_starttls() {
+        let acceptor = self_signed_acceptor(&["mx.test"], &[]);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut stream = BufReader::new(socket);
+            stream.get_mut().write_all(GREETING.as_bytes()).await.unwrap();
+            let mut received = serve_session(&mut stream, "250-mx.test\r\n250 STARTTLS\r\n").await;
+
+            let tls = acceptor.accept(stream.into_inner()).await.unwrap();
+            let mut stream = BufReader::new(tls);
+            received.extend(serve_session(&mut stream, "250 mx.test\r\n").await);
+            received
+        });
+
+        let probe = probe_smtp(addr, "mx.test", "client.test", Duration::from_secs(5)).await.unwrap();
+        let received = server.await.unwrap();
+
+        assert_eq!(received, vec!["EHLO client.test", "STARTTLS", "EHLO client.test", "QUIT"]);
+
+        let info = probe.starttls.unwrap().unwrap();
+        assert_eq!(info.certificates.len(), 1);
+        assert!(info.certificates[0].matches_hostname("mx.test"));
+        // Self-signed, so the chain can't be verified against the system roots
+        assert!(info.verification.is_err());
+    }
+}
@@ -0,0 +1,409 @@
+use anyhow::Result;
+use chrono::{DateTime, TimeZone, Utc};
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::TlsConnector;
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use x509_parser::extensions::GeneralName;
+use x509_parser::objects::{oid2sn, oid_registry};
+use x509_parser::oid_registry::Oid;
+use x509_parser::public_key::PublicKey;
+use x509_parser::x509::SubjectPublicKeyInfo;
+
+/// What we learned about a TLS session and the certificates the server sent.
+pub struct TlsInfo {
+    pub protocol: String,
+    pub cipher: String,
+    pub key_exchange: Option<String>,
+    /// Protocol chosen by ALPN, if one was offered and accepted
+    pub alpn: Option<String>,
+    /// Size of the stapled OCSP response, 0 when none was stapled
+    pub ocsp_response_len: usize,
+    /// Leaf first, in the order the server sent them
+    pub certificates: Vec<CertificateSummary>,
+    /// Outcome of verifying the chain against the system roots
+    pub verification: Result<(), String>,
+}
+
+pub struct CertificateSummary {
+    pub subject: String,
+    pub issuer: String,
+    pub names: Vec<String>,
+    /// Key algorithm and size, e.g. "RSA 2048 bits" or "EC prime256v1 256 bits"
+    pub key: String,
+    pub signature_algorithm: String,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+}
+
+impl CertificateSummary {
+    pub fn days_remaining(&self) -> i64 {
+        (self.not_after - Utc::now()).num_days()
+    }
+
+    pub fn is_self_signed(&self) -> bool {
+        self.subject == self.issuer
+    }
+
+    pub fn is_valid_now(&self) -> bool {
+        let now = Utc::now();
+        self.not_before <= now && now <= self.not_after
+    }
+
+    /// Checks `hostname` against the subject alternative names, allowing a
+    /// single leading wildcard label.
+    pub fn matches_hostname(&self, hostname: &str) -> bool {
+        let hostname = hostname.trim_end_matches('.').to_ascii_lowercase();
+
+        self.names.iter().any(|name| {
+            let name = name.trim_end_matches('.').to_ascii_lowercase();
+            if let Some(suffix) = name.strip_prefix("*.") {
+                match hostname.split_once('.') {
+                    Some((label, rest)) => !label.is_empty() && rest == suffix,
+                    None => false,
+                }
+            } else {
+                name == hostname
+            }
+        })
+    }
+}
+
+/// Performs a TLS handshake over `stream` with SNI set to `server_name`,
+/// offering the `alpn` protocols (none when empty).
+///
+/// The certificate chain is always checked against the system roots and the
+/// result recorded in [`TlsInfo::verification`]; with `require_valid` a
+/// failed check aborts the connection, otherwise the session is kept so the
+/// certificate can still be inspected.
+pub async fn handshake<S>(
+    stream: S,
+    server_name: &str,
+    require_valid: bool,
+    alpn: &[&str],
+) -> Result<(TlsStream<S>, TlsInfo)>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (config, verifier) = client_config(alpn)?;
+
+    let name = ServerName::try_from(server_name.trim_end_matches('.').to_string())
+        .map_err(|_| anyhow::anyhow!("Invalid TLS server name: {}", server_name))?;
+
+    let stream = TlsConnector::from(Arc::new(config))
+        .connect(name, stream)
+        .await
+        .map_err(|e| anyhow::anyhow!("TLS handshake failed: {}", e))?;
+
+    let verification = verifier.outcome();
+    if require_valid {
+        if let Err(ref reason) = verification {
+            return Err(anyhow::anyhow!("Certificate verification failed: {}", reason));
+        }
+    }
+
+    let (_, connection) = stream.get_ref();
+    let protocol = connection
+        .protocol_version()
+        .and_then(|version| version.as_str())
+        .map(|version| version.replace('_', "."))
+        .unwrap_or_else(|| "unknown".to_string());
+    let cipher = connection
+        .negotiated_cipher_suite()
+        .and_then(|suite| suite.suite().as_str())
+        .unwrap_or("unknown")
+        .to_string();
+    let key_exchange = connection
+        .negotiated_key_exchange_group()
+        .map(|group| format!("{:?}", group.name()));
+    let alpn = connection
+        .alpn_protocol()
+        .map(|protocol| String::from_utf8_lossy(protocol).to_string());
+    let certificates = connection
+        .peer_certificates()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|der| summarize_certificate(der).ok())
+        .collect();
+
+    let info = TlsInfo {
+        protocol,
+        cipher,
+        key_exchange,
+        alpn,
+        ocsp_response_len: verifier.ocsp_response_len(),
+        certificates,
+        verification,
+    };
+
+    Ok((stream, info))
+}
+
+/// A client configuration offering the `alpn` protocols whose verifier
+/// records the chain check instead of enforcing it.
+pub(crate) fn client_config(alpn: &[&str]) -> Result<(ClientConfig, Arc<RecordingVerifier>)> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let verifier = Arc::new(RecordingVerifier::new(provider.clone()));
+
+    let mut config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| anyhow::anyhow!("Failed to configure TLS: {}", e))?
+        .dangerous()
+        .with_custom_certificate_verifier(verifier.clone())
+        .with_no_client_auth();
+    config.alpn_protocols = alpn.iter().map(|protocol| protocol.as_bytes().to_vec()).collect();
+
+    Ok((config, verifier))
+}
+
+pub fn summarize_certificate(der: &[u8]) -> Result<CertificateSummary> {
+    let (_, cert) = x509_parser::parse_x509_certificate(der)
+        .map_err(|e| anyhow::anyhow!("Failed to parse certificate: {}", e))?;
+
+    let mut names = Vec::new();
+    if let Ok(Some(san)) = cert.subject_alternative_name() {
+        for name in &san.value.general_names {
+            match name {
+                GeneralName::DNSName(dns) => names.push(dns.to_string()),
+                GeneralName::IPAddress(bytes) => {
+                    let ip = match bytes.len() {
+                        4 => <[u8; 4]>::try_from(*bytes).ok().map(IpAddr::from),
+                        16 => <[u8; 16]>::try_from(*bytes).ok().map(IpAddr::from),
+                        _ => None,
+                    };
+                    names.extend(ip.map(|ip| ip.to_string()));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let timestamp = |seconds: i64| Utc.timestamp_opt(seconds, 0).single().unwrap_or_default();
+
+    Ok(CertificateSummary {
+        subject: cert.subject().to_string(),
+        issuer: cert.issuer().to_string(),
+        names,
+        key: describe_public_key(cert.public_key()),
+        signature_algorithm: oid_name(&cert.signature_algorithm.algorithm),
+        not_before: timestamp(cert.validity().not_before.timestamp()),
+        not_after: timestamp(cert.validity().not_after.timestamp()),
+    })
+}
+
+fn describe_public_key(spki: &SubjectPublicKeyInfo) -> String {
+    let bits = spki.parsed().map(|key| key.key_size()).unwrap_or(0);
+
+    let algorithm = match spki.parsed() {
+        Ok(PublicKey::RSA(_)) => "RSA".to_string(),
+        Ok(PublicKey::EC(_)) => {
+            // The curve is the algorithm parameter
+            let curve = spki
+                .algorithm
+                .parameters
+                .as_ref()
+                .and_then(|parameters| parameters.as_oid().ok())
+                .map(|oid| oid_name(&oid));
+            match curve {
+                Some(curve) => format!("EC {}", curve),
+                None => "EC".to_string(),
+            }
+        }
+        Ok(PublicKey::DSA(_)) => "DSA".to_string(),
+        _ => oid_name(&spki.algorithm.algorithm),
+    };
+
+    if bits > 0 {
+        format!("{} {} bits", algorithm, bits)
+    } else {
+        algorithm
+    }
+}
+
+/// Short name of a well-known OID (e.g. sha256WithRSAEncryption), else the dotted form.
+fn oid_name(oid: &Oid) -> String {
+    match oid.to_id_string().as_str() {
+        "1.3.101.112" => "Ed25519".to_string(),
+        "1.3.101.113" => "Ed448".to_string(),
+        dotted => oid2sn(oid, oid_registry())
+            .map(|name| name.to_string())
+            .unwrap_or_else(|_| dotted.to_string()),
+    }
+}
+
+fn system_roots() -> Arc<RootCertStore> {
+    static ROOTS: OnceLock<Arc<RootCertStore>> = OnceLock::new();
+
+    ROOTS
+        .get_or_init(|| {
+            let mut roots = RootCertStore::empty();
+            for cert in rustls_native_certs::load_native_certs().certs {
+                let _ = roots.add(cert);
+            }
+            Arc::new(roots)
+        })
+        .clone()
+}
+
+/// Runs the standard WebPKI checks but records the result instead of
+/// failing the handshake, so callers decide what an invalid chain means.
+#[derive(Debug)]
+pub(crate) struct RecordingVerifier {
+    inner: Option<Arc<WebPkiServerVerifier>>,
+    provider: Arc<CryptoProvider>,
+    outcome: Mutex<Option<Result<(), String>>>,
+    ocsp_response_len: Mutex<usize>,
+}
+
+impl RecordingVerifier {
+    fn new(provider: Arc<CryptoProvider>) -> Self {
+        let inner = WebPkiServerVerifier::builder_with_provider(system_roots(), provider.clone())
+            .build()
+            .ok();
+
+        Self {
+            inner,
+            provider,
+            outcome: Mutex::new(None),
+            ocsp_response_len: Mutex::new(0),
+        }
+    }
+
+    pub(crate) fn ocsp_response_len(&self) -> usize {
+        self.ocsp_response_len.lock().map(|len| *len).unwrap_or(0)
+    }
+
+    pub(crate) fn outcome(&self) -> Result<(), String> {
+        self.outcome
+            .lock()
+            .ok()
+            .and_then(|outcome| outcome.clone())
+            .unwrap_or_else(|| Err("no certificate was presented".to_string()))
+    }
+}
+
+impl ServerCertVerifier for RecordingVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let result = match self.inner {
+            Some(ref inner) => inner
+                .verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            None => Err("no trusted root certificates found on this system".to_string()),
+        };
+
+        if let Ok(mut outcome) = self.outcome.lock() {
+            *outcome = Some(result);
+        }
+        if let Ok(mut len) = self.ocsp_response_len.lock() {
+            *len = ocsp_response.len();
+        }
+
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// A TLS server side for tests, presenting a self-signed certificate for
+/// `names` and accepting the `alpn` protocols.
+#[cfg(test)]
+pub(crate) fn self_signed_acceptor(names: &[&str], alpn: &[&str]) -> tokio_rustls::TlsAcceptor {
+    tokio_rustls::TlsAcceptor::from(Arc::new(self_signed_server_config(names, alpn)))
+}
+
+#[cfg(test)]
+pub(crate) fn self_signed_server_config(names: &[&str], alpn: &[&str]) -> rustls::ServerConfig {
+    use rustls::pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer};
+
+    let names = names.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+    let certified = rcgen::generate_simple_self_signed(names).unwrap();
+    let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(certified.key_pair.serialize_der()));
+    let mut config = rustls::ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(vec![certified.cert.der().clone()], key)
+        .unwrap();
+    config.alpn_protocols = alpn.iter().map(|protocol| protocol.as_bytes().to_vec()).collect();
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(names: &[&str]) -> CertificateSummary {
+        CertificateSummary {
+            subject: "CN=test".to_string(),
+            issuer: "CN=test".to_string(),
+            names: names.iter().map(|name| name.to_string()).collect(),
+            key: "EC prime256v1 256 bits".to_string(),
+            signature_algorithm: "ecdsa-with-SHA256".to_string(),
+            not_before: Utc::now(),
+            not_after: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_matches_hostname_exact() {
+        let cert = summary(&["mail.example.com", "example.com"]);
+        assert!(cert.matches_hostname("mail.example.com"));
+        assert!(cert.matches_hostname("EXAMPLE.com."));
+        assert!(!cert.matches_hostname("www.example.com"));
+    }
+
+    #[test]
+    fn test_matches_hostname_wildcard() {
+        let cert = summary(&["*.example.com"]);
+        assert!(cert.matches_hostname("mx1.example.com"));
+        assert!(!cert.matches_hostname("example.com"));
+        assert!(!cert.matches_hostname("a.b.example.com"));
+    }
+
+    #[test]
+    fn test_summarize_certificate_key_and_signature() {
+        let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert = summarize_certificate(certified.cert.der()).unwrap();
+
+        assert_eq!(cert.key, "EC prime256v1 256 bits");
+        assert_eq!(cert.signature_algorithm, "ecdsa-with-SHA256");
+        assert_eq!(cert.names, vec!["localhost".to_string()]);
+        assert!(cert.is_self_signed());
+        assert!(cert.is_valid_now());
+    }
+}
@@ -41,7 +41,7 @@ impl Traceroute {
         let socket = UdpSocket::bind(local_addr).await?;
         
         // Set TTL for this hop
-        if let Err(_) = set_ttl(&socket, hop_number as u32) {
+        if set_ttl(&socket, hop_number).is_err() {
             return Ok(None);
         }
         
@@ -0,0 +1,14 @@
+use std::fmt;
+
+/// An error the command has already reported to the user; `main` ends the
+/// process with this status instead of printing the error again.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExitStatus(pub i32);
+
+impl fmt::Display for ExitStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "exit status {}", self.0)
+    }
+}
+
+impl std::error::Error for ExitStatus {}
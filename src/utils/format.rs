@@ -31,4 +31,41 @@ pub fn format_bytes(bytes: u64) -> String {
     } else {
         format!("{:.2} {}", size, UNITS[unit_index])
     }
-}
\ No newline at end of file
+}
+
+/// Formats bytes as a classic hex dump: offset, 16 hex bytes, then ASCII.
+pub fn format_hex_dump(data: &[u8]) -> String {
+    let mut lines = Vec::new();
+
+    for (index, chunk) in data.chunks(16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let ascii: String = chunk
+            .iter()
+            .map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' })
+            .collect();
+
+        lines.push(format!("{:04x}  {:<47}  {}", index * 16, hex.join(" "), ascii));
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_hex_dump() {
+        let dump = format_hex_dump(b"netdiag\x00\x01 hex dump test");
+        let lines: Vec<&str> = dump.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], "0000  6e 65 74 64 69 61 67 00 01 20 68 65 78 20 64 75  netdiag.. hex du");
+        assert_eq!(lines[1], format!("0010  {:<47}  mp test", "6d 70 20 74 65 73 74"));
+    }
+
+    #[test]
+    fn test_format_hex_dump_empty() {
+        assert_eq!(format_hex_dump(&[]), "");
+    }
+}
@@ -1,2 +1,4 @@
+pub mod exit;
 pub mod format;
-pub mod ports;
\ No newline at end of file
+pub mod ports;
+pub mod stats;
\ No newline at end of file
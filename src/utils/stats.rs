@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+/// Returns the `p`th percentile (0.0-100.0) of `samples` using the
+/// nearest-rank method, or `None` when there are no samples.
+pub fn percentile(samples: &[Duration], p: f64) -> Option<Duration> {
+    if samples.is_empty() {
+        return None;
+    }
+
+    let mut sorted = samples.to_vec();
+    sorted.sort();
+
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    let index = rank.clamp(1, sorted.len()) - 1;
+
+    Some(sorted[index])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|v| Duration::from_millis(*v)).collect()
+    }
+
+    #[test]
+    fn test_percentile_empty() {
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let samples = millis(&[40, 10, 30, 20, 50]);
+        assert_eq!(percentile(&samples, 50.0), Some(Duration::from_millis(30)));
+        assert_eq!(percentile(&samples, 95.0), Some(Duration::from_millis(50)));
+        assert_eq!(percentile(&samples, 100.0), Some(Duration::from_millis(50)));
+        assert_eq!(percentile(&samples, 0.0), Some(Duration::from_millis(10)));
+    }
+}